@@ -0,0 +1,247 @@
+#![doc(html_root_url = "https://docs.rs/linked-futures-macros/0.1.0")]
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+//! Procedural versions of the [`linked-futures`](https://crates.io/crates/linked-futures)
+//! macros.
+//!
+//! They generate the same code as the declarative macros, but validate the input first and
+//! emit the code of every linked future with the span of its expression, so compile errors
+//! point at the offending variant or future expression instead of the macro invocation.
+//!
+//! The macros are re-exported from `linked_futures::macros` when the `macros` feature
+//! of `linked-futures` is enabled.
+
+use std::collections::HashSet;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Error, Expr, Fields, Ident, ItemEnum, Token};
+
+/// Turn a fieldless enum into the identifier enum of a linked block, generating
+/// the one-of type with the name passed as an argument.
+///
+/// The name of the output enum may be passed as the second argument. The visibility
+/// of the enum is applied to all of the generated types.
+#[proc_macro_attribute]
+pub fn linked_block(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as LinkedBlockArgs);
+    let identifier_enum = parse_macro_input!(input as ItemEnum);
+
+    expand_linked_block(args, identifier_enum)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Declare and link multiple futures in a single step, with the same syntax as the
+/// declarative `linked!` macro.
+#[proc_macro]
+pub fn linked(input: TokenStream) -> TokenStream {
+    let linked = parse_macro_input!(input as Linked);
+
+    expand_linked(linked)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn expand_linked_block(
+    args: LinkedBlockArgs,
+    identifier_enum: ItemEnum,
+) -> syn::Result<TokenStream2> {
+    let LinkedBlockArgs {
+        one_of_block,
+        output_enum,
+    } = args;
+    let ItemEnum {
+        attrs,
+        vis,
+        ident,
+        generics,
+        variants,
+        ..
+    } = identifier_enum;
+
+    let mut errors = Vec::new();
+    if !generics.params.is_empty() || generics.where_clause.is_some() {
+        errors.push(Error::new(
+            generics.span(),
+            "linked block identifier can't have generic parameters",
+        ));
+    }
+    if variants.is_empty() {
+        errors.push(Error::new(
+            ident.span(),
+            "linked block should have at least one variant",
+        ));
+    }
+    for variant in &variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            errors.push(Error::new(
+                variant.fields.span(),
+                format!("linked future `{}` can't have fields", variant.ident),
+            ));
+        }
+        if let Some((_, discriminant)) = &variant.discriminant {
+            errors.push(Error::new(
+                discriminant.span(),
+                format!(
+                    "linked future `{}` can't have a discriminant",
+                    variant.ident
+                ),
+            ));
+        }
+    }
+    combine(errors)?;
+
+    let variants = variants.into_iter().map(|variant| {
+        let attrs = variant.attrs;
+        let ident = variant.ident;
+        quote!(#(#attrs)* #ident)
+    });
+
+    let output_enum = output_enum.map(|output_enum| quote!(, #output_enum));
+
+    Ok(quote! {
+        ::linked_futures::linked_block!(
+            #vis #one_of_block,
+            #(#attrs)* #ident #output_enum;
+            #(#variants),*
+        );
+    })
+}
+
+fn expand_linked(linked: Linked) -> syn::Result<TokenStream2> {
+    let Linked {
+        name,
+        identifier_enum,
+        futures,
+    } = linked;
+
+    let mut errors = Vec::new();
+    if futures.is_empty() {
+        errors.push(Error::new(
+            Span::call_site(),
+            "at least one future should be linked",
+        ));
+    }
+    let mut seen = HashSet::new();
+    for future in &futures {
+        if !seen.insert(future.key.to_string()) {
+            errors.push(Error::new(
+                future.key.span(),
+                format!("future `{}` is linked more than once", future.key),
+            ));
+        }
+    }
+    combine(errors)?;
+
+    let keys = futures.iter().map(|future| &future.key).collect::<Vec<_>>();
+    let bindings = (0..keys.len())
+        .map(|index| format_ident!("__linked_future_{}", index))
+        .collect::<Vec<_>>();
+    let indices = 0..keys.len();
+    let first = &bindings[0];
+    let declarations = futures.iter().zip(&bindings).map(|(future, binding)| {
+        let LinkedFuture { key, value } = future;
+        quote_spanned! {value.span()=>
+            let #binding = async {
+                (#identifier_enum::#key, #value.await)
+            };
+            ::linked_futures::__private::assert_same_output(&#first, &#binding);
+        }
+    });
+
+    Ok(quote! {
+        ::linked_futures::__linked_identifier!(#identifier_enum; #(#keys),*);
+
+        let #name = {
+            ::linked_futures::__linked_one_of!(__LinkedFuturesBlock; #(#keys),*);
+
+            #(#declarations)*
+            let mut __linked = ::linked_futures::FuturesUnordered::new();
+            #(
+                __linked.push(::linked_futures::__private::Declared::new(
+                    #indices,
+                    __LinkedFuturesBlock::#keys(#bindings),
+                ));
+            )*
+            ::linked_futures::LinkedBlock::new(__linked)
+        };
+    })
+}
+
+fn combine(errors: Vec<Error>) -> syn::Result<()> {
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        None => Ok(()),
+        Some(mut first) => {
+            for error in errors {
+                first.combine(error);
+            }
+            Err(first)
+        }
+    }
+}
+
+struct LinkedBlockArgs {
+    one_of_block: Ident,
+    output_enum: Option<Ident>,
+}
+
+impl Parse for LinkedBlockArgs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let one_of_block = input.parse()?;
+        let output_enum = if input.is_empty() {
+            None
+        } else {
+            input.parse::<Token![,]>()?;
+            Some(input.parse()?)
+        };
+
+        Ok(LinkedBlockArgs {
+            one_of_block,
+            output_enum,
+        })
+    }
+}
+
+struct Linked {
+    name: Ident,
+    identifier_enum: Ident,
+    futures: Punctuated<LinkedFuture, Token![,]>,
+}
+
+impl Parse for Linked {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        input.parse::<Token![let]>()?;
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let identifier_enum = input.parse()?;
+        input.parse::<Token![;]>()?;
+        let futures = Punctuated::parse_terminated(input)?;
+
+        Ok(Linked {
+            name,
+            identifier_enum,
+            futures,
+        })
+    }
+}
+
+struct LinkedFuture {
+    key: Ident,
+    value: Expr,
+}
+
+impl Parse for LinkedFuture {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let value = input.parse()?;
+
+        Ok(LinkedFuture { key, value })
+    }
+}
@@ -0,0 +1,172 @@
+use core::fmt;
+use core::future::Future;
+use std::sync::{Arc, Mutex};
+
+use futures::future::{self, AbortHandle, Aborted};
+
+/// Registry of the handles aborting single linked futures, without terminating the block
+///
+/// Futures are registered by marking them with `#[abortable(handles)]` in the linking
+/// macros. An aborted future is dropped, and never completes, as if it was skipped:
+/// ```rust
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use futures::future::{join, pending};
+/// use futures::StreamExt;
+///
+/// use linked_futures::{link_futures, linked_block, AbortHandles};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Stop);
+///
+/// let (tx, mut rx) = mpsc::unbounded();
+/// let handles = AbortHandles::new().on_abort(move |id| tx.unbounded_send(id).unwrap());
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     #[abortable(handles)]
+///     Reader => pending(),
+///     Stop => rx.next()
+/// );
+/// let abort = async {
+///     assert!(handles.abort(&BlockFutureIdentifier::Reader));
+/// };
+/// let (completed, ()) = block_on(join(block, abort));
+/// assert_eq!(
+///     completed,
+///     (BlockFutureIdentifier::Stop, Some(BlockFutureIdentifier::Reader))
+/// );
+/// ```
+pub struct AbortHandles<I> {
+    inner: Arc<Mutex<Inner<I>>>,
+}
+
+struct Inner<I> {
+    next_token: usize,
+    handles: Vec<(usize, I, AbortHandle)>,
+    on_abort: Option<Box<dyn FnMut(I) + Send>>,
+}
+
+impl<I> Clone for AbortHandles<I> {
+    fn clone(&self) -> Self {
+        AbortHandles {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<I> Default for AbortHandles<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I: fmt::Debug> fmt::Debug for AbortHandles<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_list()
+            .entries(inner.handles.iter().map(|(_, identifier, _)| identifier))
+            .finish()
+    }
+}
+
+impl<I> AbortHandles<I> {
+    /// Create the registry without registered futures
+    pub fn new() -> Self {
+        AbortHandles {
+            inner: Arc::new(Mutex::new(Inner {
+                next_token: 0,
+                handles: Vec::new(),
+                on_abort: None,
+            })),
+        }
+    }
+
+    /// Call `on_abort` with the identifier of every aborted future, once it's dropped
+    pub fn on_abort(self, on_abort: impl FnMut(I) + Send + 'static) -> Self {
+        self.inner.lock().unwrap().on_abort = Some(Box::new(on_abort));
+        self
+    }
+
+    fn unregister(&self, token: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.handles.retain(|(other, _, _)| *other != token);
+    }
+
+    fn aborted(&self, identifier: I) {
+        let on_abort = self.inner.lock().unwrap().on_abort.take();
+        if let Some(mut on_abort) = on_abort {
+            on_abort(identifier);
+            let mut inner = self.inner.lock().unwrap();
+            if inner.on_abort.is_none() {
+                inner.on_abort = Some(on_abort);
+            }
+        }
+    }
+}
+
+impl<I: PartialEq> AbortHandles<I> {
+    /// Abort the running futures registered with `identifier`. Returns `false` if there
+    /// are none.
+    pub fn abort(&self, identifier: &I) -> bool {
+        let inner = self.inner.lock().unwrap();
+        let mut is_aborted = false;
+        for (_, _, handle) in inner
+            .handles
+            .iter()
+            .filter(|(_, other, _)| other == identifier)
+        {
+            handle.abort();
+            is_aborted = true;
+        }
+        is_aborted
+    }
+
+    /// Returns `true` if a running future is registered with `identifier`
+    pub fn contains(&self, identifier: &I) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .handles
+            .iter()
+            .any(|(_, other, _)| other == identifier)
+    }
+}
+
+impl<I: Clone> AbortHandles<I> {
+    /// Register `future` with `identifier`, resolving with its output unless it's aborted.
+    /// Aborted futures never complete.
+    pub async fn register<F: Future>(&self, identifier: I, future: F) -> F::Output {
+        let (handle, registration) = AbortHandle::new_pair();
+        let token = {
+            let mut inner = self.inner.lock().unwrap();
+            let token = inner.next_token;
+            inner.next_token += 1;
+            inner.handles.push((token, identifier.clone(), handle));
+            token
+        };
+
+        let guard = Unregister {
+            handles: self,
+            token,
+        };
+        let result = future::Abortable::new(future, registration).await;
+        drop(guard);
+
+        match result {
+            Ok(output) => output,
+            Err(Aborted) => {
+                self.aborted(identifier);
+                future::pending().await
+            }
+        }
+    }
+}
+
+/// Unregisters the future once it completes or is dropped
+struct Unregister<'a, I> {
+    handles: &'a AbortHandles<I>,
+    token: usize,
+}
+
+impl<I> Drop for Unregister<'_, I> {
+    fn drop(&mut self) {
+        self.handles.unregister(self.token);
+    }
+}
@@ -0,0 +1,80 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+
+use crate::LinkedBlock;
+
+/// Future returned by [`LinkedBlock::all_ready`](struct.LinkedBlock.html#method.all_ready),
+/// which resolves with the outputs of all of the futures completed in the terminating poll
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct AllReady<F> {
+    futures: FuturesUnordered<F>,
+    is_terminated: bool,
+}
+
+impl<F: Future> LinkedBlock<F> {
+    /// Resolve with the outputs of every future which completed in the poll terminating the
+    /// block, instead of only the first one. The vector is never empty, and the futures
+    /// completed later are dropped with the block.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block};
+    ///
+    /// linked_block!(Block, BlockFutureIdentifier; Reader, Writer, Stop);
+    ///
+    /// let block = link_futures!(Block, BlockFutureIdentifier;
+    ///     Reader => async { 1 },
+    ///     Writer => async { 2 },
+    ///     Stop => pending()
+    /// );
+    /// let mut completed = block_on(block.all_ready());
+    /// completed.sort();
+    /// assert_eq!(
+    ///     completed,
+    ///     [(BlockFutureIdentifier::Reader, 1), (BlockFutureIdentifier::Writer, 2)]
+    /// );
+    /// ```
+    pub fn all_ready(self) -> AllReady<F> {
+        AllReady {
+            futures: self.into_inner(),
+            is_terminated: false,
+        }
+    }
+}
+
+impl<F: Future> Future for AllReady<F> {
+    type Output = Vec<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        assert!(!self.is_terminated, "AllReady polled after completion");
+
+        let first = match self.futures.poll_next_unpin(cx) {
+            Poll::Ready(Some(output)) => output,
+            // None of the futures is left to terminate the block
+            Poll::Ready(None) => return Poll::Pending,
+            Poll::Pending => return Poll::Pending,
+        };
+        let mut completed = vec![first];
+        while let Poll::Ready(Some(output)) = self.futures.poll_next_unpin(cx) {
+            completed.push(output);
+        }
+
+        self.is_terminated = true;
+        Poll::Ready(completed)
+    }
+}
+
+impl<F: Future> FusedFuture for AllReady<F> {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
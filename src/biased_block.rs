@@ -0,0 +1,65 @@
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+
+/// Future returned by [`link_biased_futures`](macro.link_biased_futures.html), which polls
+/// the linked futures in the order of declaration and resolves with the output of the first
+/// completed future. A block without futures never resolves.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LinkedBiasedBlock<F> {
+    futures: Vec<F>,
+    is_terminated: bool,
+}
+
+impl<F: Future> LinkedBiasedBlock<F> {
+    /// Link the futures into a block, which polls them in the order of the vector
+    pub fn new(futures: Vec<F>) -> Self {
+        LinkedBiasedBlock {
+            futures,
+            is_terminated: false,
+        }
+    }
+}
+
+impl<F> LinkedBiasedBlock<F> {
+    /// Number of the linked futures
+    pub fn len(&self) -> usize {
+        self.futures.len()
+    }
+
+    /// Returns `true` if no futures are linked
+    pub fn is_empty(&self) -> bool {
+        self.futures.is_empty()
+    }
+}
+
+impl<F: Future> Future for LinkedBiasedBlock<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The futures are structurally pinned. The vector is never resized, so they are
+        // never moved in memory.
+        let this = unsafe { self.get_unchecked_mut() };
+        assert!(
+            !this.is_terminated,
+            "LinkedBiasedBlock polled after completion"
+        );
+        for future in &mut this.futures {
+            if let Poll::Ready(output) = unsafe { Pin::new_unchecked(future) }.poll(cx) {
+                this.is_terminated = true;
+                return Poll::Ready(output);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl<F: Future> FusedFuture for LinkedBiasedBlock<F> {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
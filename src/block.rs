@@ -0,0 +1,116 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+
+/// Future returned by [`link_futures`](macro.link_futures.html), which resolves with the
+/// output of the first completed future. A block without futures, such as a block with every
+/// future disabled by a guard, never resolves.
+///
+/// Unlike an `async` block, the block has a nameable type, so it can be stored in a field of
+/// a struct which is generic over the linked futures:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, LinkedBlock};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Never, Stop);
+///
+/// struct Service<F> {
+///     block: LinkedBlock<F>,
+/// }
+///
+/// let service = Service {
+///     block: link_futures!(Block, BlockFutureIdentifier;
+///         Never => pending::<()>(),
+///         Stop => async {}
+///     ),
+/// };
+/// let (completed_future_identifier, _) = block_on(service.block);
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Stop);
+/// ```
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LinkedBlock<F> {
+    futures: FuturesUnordered<F>,
+    is_terminated: bool,
+}
+
+impl<F: Future> LinkedBlock<F> {
+    /// Link the futures into a block
+    pub fn new(futures: FuturesUnordered<F>) -> Self {
+        LinkedBlock {
+            futures,
+            is_terminated: false,
+        }
+    }
+}
+
+impl<F> LinkedBlock<F> {
+    /// Consume the block, returning the futures which are not yet completed
+    pub fn into_inner(self) -> FuturesUnordered<F> {
+        self.futures
+    }
+
+    pub(crate) fn futures_mut(&mut self) -> &mut FuturesUnordered<F> {
+        &mut self.futures
+    }
+}
+
+impl<F, I, T> LinkedBlock<F>
+where
+    F: Future<Output = (I, T)>,
+{
+    /// Poll the futures until `handle` returns the output terminating the block, or until
+    /// all of the futures complete
+    pub(crate) fn poll_until(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut handle: impl FnMut(I, T) -> Option<(I, T)>,
+    ) -> Poll<Option<(I, T)>> {
+        assert!(!self.is_terminated, "LinkedBlock polled after completion");
+
+        loop {
+            match self.futures.poll_next_unpin(cx) {
+                Poll::Ready(Some((identifier, output))) => {
+                    if let Some(output) = handle(identifier, output) {
+                        self.is_terminated = true;
+                        return Poll::Ready(Some(output));
+                    }
+                }
+                Poll::Ready(None) => {
+                    self.is_terminated = true;
+                    return Poll::Ready(None);
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<F: Future> Future for LinkedBlock<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        assert!(!self.is_terminated, "LinkedBlock polled after completion");
+
+        match self.futures.poll_next_unpin(cx) {
+            Poll::Ready(Some(output)) => {
+                self.is_terminated = true;
+                Poll::Ready(output)
+            }
+            // Every future is disabled by a guard, so none of them may terminate the block
+            Poll::Ready(None) => Poll::Pending,
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<F: Future> FusedFuture for LinkedBlock<F> {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
@@ -0,0 +1,120 @@
+use alloc::vec::Vec;
+use core::future::Future;
+
+use futures::future::{BoxFuture, FutureExt};
+use futures::stream::FuturesUnordered;
+
+use crate::{Completions, LinkedBlock, LinkedJoinBlock};
+
+/// Builder linking boxed futures into a block without the linking macros, so the futures may
+/// be linked conditionally, or in a loop
+///
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{linked_block, LinkedBlockBuilder};
+///
+/// linked_block!(Proxy, ProxyFutureIdentifier; Reader, Writer, Metrics);
+///
+/// let with_metrics = false;
+/// let mut builder = LinkedBlockBuilder::new()
+///     .add(ProxyFutureIdentifier::Reader, pending())
+///     .add(ProxyFutureIdentifier::Writer, async { "closed" });
+/// if with_metrics {
+///     builder = builder.add(ProxyFutureIdentifier::Metrics, pending());
+/// }
+/// assert_eq!(builder.len(), 2);
+/// assert_eq!(
+///     block_on(builder.race()),
+///     (ProxyFutureIdentifier::Writer, "closed")
+/// );
+/// ```
+#[must_use = "builders do nothing unless a block is built"]
+pub struct LinkedBlockBuilder<I, T> {
+    futures: Vec<(I, BoxFuture<'static, T>)>,
+}
+
+impl<I, T> core::fmt::Debug for LinkedBlockBuilder<I, T>
+where
+    I: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list()
+            .entries(self.futures.iter().map(|(identifier, _)| identifier))
+            .finish()
+    }
+}
+
+impl<I, T> Default for LinkedBlockBuilder<I, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, T> LinkedBlockBuilder<I, T> {
+    /// Create the builder without futures
+    pub fn new() -> Self {
+        LinkedBlockBuilder {
+            futures: Vec::new(),
+        }
+    }
+
+    /// Number of the added futures
+    pub fn len(&self) -> usize {
+        self.futures.len()
+    }
+
+    /// Returns `true` if no futures are added
+    pub fn is_empty(&self) -> bool {
+        self.futures.is_empty()
+    }
+}
+
+impl<I, T> LinkedBlockBuilder<I, T>
+where
+    I: Send + 'static,
+    T: 'static,
+{
+    /// Box `future`, and add it to the block tagged with `identifier`
+    pub fn add<F>(mut self, identifier: I, future: F) -> Self
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        self.futures.push((identifier, future.boxed()));
+        self
+    }
+
+    /// Build the block resolving with the output of the first completed future, as the block
+    /// returned by [`link_futures`](macro.link_futures.html)
+    pub fn race(self) -> LinkedBlock<BoxFuture<'static, (I, T)>> {
+        LinkedBlock::new(
+            self.futures
+                .into_iter()
+                .map(|(identifier, future)| future.map(move |output| (identifier, output)).boxed())
+                .collect::<FuturesUnordered<_>>(),
+        )
+    }
+
+    /// Build the block resolving with the outputs of all of the futures, as the block returned
+    /// by [`join_futures`](macro.join_futures.html)
+    pub fn join(self) -> LinkedJoinBlock<BoxFuture<'static, Option<(I, T)>>, I, T>
+    where
+        I: Ord,
+    {
+        LinkedJoinBlock::new(
+            self.futures
+                .into_iter()
+                .map(|(identifier, future)| {
+                    future.map(move |output| Some((identifier, output))).boxed()
+                })
+                .collect::<FuturesUnordered<_>>(),
+        )
+    }
+
+    /// Build the stream of the outputs of all of the futures, as returned by
+    /// [`LinkedBlock::into_stream`](struct.LinkedBlock.html#method.into_stream)
+    pub fn stream(self) -> Completions<BoxFuture<'static, (I, T)>> {
+        self.race().into_stream()
+    }
+}
@@ -0,0 +1,134 @@
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::panic::{self, AssertUnwindSafe};
+
+use futures::future::FusedFuture;
+
+use crate::{LinkedBlock, Panicked};
+
+/// Handling of the panics of linked futures marked with `#[on_panic(policy)]`
+///
+/// The marked futures are linked as a factory closure, like with `#[restart]`, so that
+/// they may be restarted:
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, PanicPolicy};
+///
+/// linked_block!(Service, ServiceFutureIdentifier; Worker, Listen);
+///
+/// let runs = AtomicUsize::new(0);
+/// let block = link_futures!(Service, ServiceFutureIdentifier;
+///     #[on_panic(PanicPolicy::Restart)]
+///     Worker => || async {
+///         if runs.fetch_add(1, Ordering::SeqCst) < 2 {
+///             panic!("worker crashed");
+///         }
+///         "recovered"
+///     },
+///     Listen => pending()
+/// );
+/// let (id, output) = block_on(block.catch_unwind()).unwrap();
+/// assert_eq!(id, ServiceFutureIdentifier::Worker);
+/// assert_eq!(output, "recovered");
+/// assert_eq!(runs.load(Ordering::SeqCst), 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PanicPolicy {
+    /// Unwind the block, as if the future wasn't marked
+    Propagate,
+    /// Abort the process
+    Abort,
+    /// Complete the block with [`Panicked`](struct.Panicked.html), like `#[catch_unwind]`
+    Complete,
+    /// Drop the panic, and restart the future with the factory
+    Restart,
+}
+
+/// Future returned by [`LinkedBlock::catch_unwind`](struct.LinkedBlock.html#method.catch_unwind),
+/// which resolves with the identifier of the panicked future instead of unwinding
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct CatchUnwind<F, I> {
+    block: LinkedBlock<F>,
+    is_terminated: bool,
+    identifier: PhantomData<fn() -> I>,
+}
+
+impl<F, I, T> LinkedBlock<F>
+where
+    F: Future<Output = (I, T)>,
+    I: 'static,
+{
+    /// Resolve with `Err(Panicked)` if one of the linked futures marked with `#[catch_unwind]`
+    /// panics, instead of unwinding. Panics of the rest of the futures are not caught.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block, Panicked};
+    ///
+    /// linked_block!(Service, ServiceFutureIdentifier; Parser, Listen);
+    ///
+    /// let block = link_futures!(Service, ServiceFutureIdentifier;
+    ///     #[catch_unwind]
+    ///     Parser => async { panic!("malformed input") },
+    ///     Listen => pending()
+    /// );
+    /// let Panicked(id, payload) = block_on(block.catch_unwind()).unwrap_err();
+    /// assert_eq!(id, ServiceFutureIdentifier::Parser);
+    /// assert_eq!(*payload.downcast::<&str>().unwrap(), "malformed input");
+    /// ```
+    pub fn catch_unwind(self) -> CatchUnwind<F, I> {
+        CatchUnwind {
+            block: self,
+            is_terminated: false,
+            identifier: PhantomData,
+        }
+    }
+}
+
+impl<F, I, T> Future for CatchUnwind<F, I>
+where
+    F: Future<Output = (I, T)>,
+    I: 'static,
+{
+    type Output = Result<(I, T), Panicked<I>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        assert!(!self.is_terminated, "CatchUnwind polled after completion");
+
+        let block = &mut self.block;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| Pin::new(block).poll(cx)));
+        match result {
+            Ok(Poll::Ready(output)) => {
+                self.is_terminated = true;
+                Poll::Ready(Ok(output))
+            }
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => match payload.downcast::<Panicked<I>>() {
+                Ok(panicked) => {
+                    self.is_terminated = true;
+                    Poll::Ready(Err(*panicked))
+                }
+                Err(payload) => panic::resume_unwind(payload),
+            },
+        }
+    }
+}
+
+impl<F, I, T> FusedFuture for CatchUnwind<F, I>
+where
+    F: Future<Output = (I, T)>,
+    I: 'static,
+{
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
@@ -0,0 +1,100 @@
+use core::fmt;
+use core::time::Duration;
+
+/// State of a [`CircuitBreaker`](struct.CircuitBreaker.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// The future is running, and restarted once it completes
+    Closed,
+    /// The future failed too many times in a row, and isn't running during the cool-down
+    Open,
+    /// The cool-down elapsed, and the future is restarted once to probe whether it recovered
+    HalfOpen,
+}
+
+/// Restart policy of linked futures marked with `#[circuit_breaker(breaker)]`
+///
+/// The marked futures are linked as a factory closure of futures returning `Result`, and are
+/// restarted once they complete, like with `#[restart]`. After `failures` errors in a row,
+/// the circuit is opened, and the future isn't restarted until the cool-down elapses. Then
+/// the future is restarted once: the circuit is closed if it succeeds, and opened again
+/// otherwise. The future never terminates the block, and requires the `tokio` feature unless
+/// the block is given another timer with `timer = T`.
+/// ```rust
+/// use std::io;
+/// use std::time::Duration;
+///
+/// use futures::channel::mpsc;
+/// use futures::StreamExt;
+///
+/// use linked_futures::{link_futures, linked_block, CircuitBreaker, CircuitState};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Poller, Stop);
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main]
+/// async fn main() {
+///     let (tx, rx) = mpsc::unbounded();
+///     let breaker = CircuitBreaker::new(2, Duration::from_millis(1))
+///         .on_transition(move |state| tx.unbounded_send(state).unwrap());
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         #[circuit_breaker(breaker)]
+///         Poller => || async { Err::<(), _>(io::Error::from(io::ErrorKind::ConnectionRefused)) },
+///         Stop => rx.take(3).collect::<Vec<_>>()
+///     );
+///     let (_, transitions) = block.await;
+///     assert_eq!(
+///         transitions,
+///         [CircuitState::Open, CircuitState::HalfOpen, CircuitState::Open]
+///     );
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+pub struct CircuitBreaker {
+    failures: u32,
+    cool_down: Duration,
+    on_transition: Option<Box<dyn FnMut(CircuitState) + Send>>,
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("failures", &self.failures)
+            .field("cool_down", &self.cool_down)
+            .finish()
+    }
+}
+
+impl CircuitBreaker {
+    /// Open the circuit for `cool_down` after `failures` errors in a row
+    pub fn new(failures: u32, cool_down: Duration) -> Self {
+        CircuitBreaker {
+            failures,
+            cool_down,
+            on_transition: None,
+        }
+    }
+
+    /// Call `on_transition` with the new state whenever the state is changed
+    pub fn on_transition(self, on_transition: impl FnMut(CircuitState) + Send + 'static) -> Self {
+        CircuitBreaker {
+            on_transition: Some(Box::new(on_transition)),
+            ..self
+        }
+    }
+
+    pub(crate) fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub(crate) fn cool_down(&self) -> Duration {
+        self.cool_down
+    }
+
+    pub(crate) fn transition(&mut self, state: CircuitState) {
+        if let Some(on_transition) = &mut self.on_transition {
+            on_transition(state);
+        }
+    }
+}
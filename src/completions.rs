@@ -0,0 +1,80 @@
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::Future;
+use futures::stream::{FusedStream, FuturesUnordered, Stream, StreamExt};
+
+use crate::LinkedBlock;
+
+/// Stream returned by [`LinkedBlock::into_stream`](struct.LinkedBlock.html#method.into_stream),
+/// which yields the output of every linked future as it completes
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Completions<F> {
+    futures: FuturesUnordered<F>,
+}
+
+impl<F: Future> LinkedBlock<F> {
+    /// Turn the block into a stream of the outputs of all of the linked futures, in the order
+    /// of completion. The stream ends once all of the futures complete.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::StreamExt;
+    ///
+    /// use linked_futures::{link_futures, linked_block};
+    ///
+    /// linked_block!(Block, BlockFutureIdentifier; Reader, Writer);
+    ///
+    /// let block = link_futures!(Block, BlockFutureIdentifier;
+    ///     Reader => async { 1 },
+    ///     Writer => async { 2 }
+    /// );
+    /// let mut completions = block_on(block.into_stream().collect::<Vec<_>>());
+    /// completions.sort();
+    /// assert_eq!(
+    ///     completions,
+    ///     [(BlockFutureIdentifier::Reader, 1), (BlockFutureIdentifier::Writer, 2)]
+    /// );
+    /// ```
+    pub fn into_stream(self) -> Completions<F> {
+        Completions {
+            futures: self.into_inner(),
+        }
+    }
+}
+
+impl<F> Completions<F> {
+    /// Number of the futures which are not yet completed
+    pub fn len(&self) -> usize {
+        self.futures.len()
+    }
+
+    /// Returns `true` if all of the futures are completed
+    pub fn is_empty(&self) -> bool {
+        self.futures.is_empty()
+    }
+
+    /// Consume the stream, returning the futures which are not yet completed
+    pub fn into_inner(self) -> FuturesUnordered<F> {
+        self.futures
+    }
+}
+
+impl<F: Future> Stream for Completions<F> {
+    type Item = F::Output;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.futures.poll_next_unpin(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.futures.size_hint()
+    }
+}
+
+impl<F: Future> FusedStream for Completions<F> {
+    fn is_terminated(&self) -> bool {
+        self.futures.is_terminated()
+    }
+}
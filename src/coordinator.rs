@@ -0,0 +1,196 @@
+use core::task::{Poll, Waker};
+use std::sync::{Arc, Mutex};
+
+use futures::future::poll_fn;
+
+use crate::{Shutdown, Signalled};
+
+/// Root of a tree of blocks, which shuts them down from the top
+///
+/// Child blocks are handed a [`ShutdownToken`](struct.ShutdownToken.html) created with
+/// [`child`](#method.child). [`shutdown`](#method.shutdown) fires the signals of all of the
+/// tokens in the tree, and resolves once every token is dropped, acknowledging that its block
+/// is stopped. Tokens may hand out tokens to their own children, and a token only
+/// acknowledges the shutdown once its children acknowledge it too.
+/// ```rust
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+///
+/// use futures::executor::block_on;
+/// use futures::future::{join, pending};
+///
+/// use linked_futures::{link_futures, linked_block, ShutdownCoordinator};
+///
+/// linked_block!(Worker, WorkerFutureIdentifier; Work, Stop);
+///
+/// let coordinator = ShutdownCoordinator::new();
+/// let token = coordinator.child();
+/// let is_flushed = Arc::new(AtomicBool::new(false));
+/// let flushed = is_flushed.clone();
+/// let worker = async move {
+///     let block = link_futures!(Worker, WorkerFutureIdentifier;
+///         Work => pending(),
+///         Stop => token.signalled()
+///     );
+///     block.await;
+///     flushed.store(true, Ordering::SeqCst);
+///     drop(token);
+/// };
+/// let parent = async {
+///     coordinator.shutdown().await;
+///     assert!(is_flushed.load(Ordering::SeqCst));
+/// };
+/// block_on(join(worker, parent));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownCoordinator {
+    node: Arc<Node>,
+}
+
+/// Shutdown signal of a child block, created by
+/// [`ShutdownCoordinator::child`](struct.ShutdownCoordinator.html#method.child). Dropping the
+/// token acknowledges that the block is stopped.
+#[derive(Debug)]
+pub struct ShutdownToken {
+    node: Arc<Node>,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    parent: Option<Arc<Node>>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    shutdown: Shutdown,
+    children: Vec<Arc<Node>>,
+    is_released: bool,
+    wakers: Vec<Waker>,
+}
+
+impl Node {
+    fn child(self: &Arc<Self>) -> ShutdownToken {
+        let node = Arc::new(Node {
+            parent: Some(self.clone()),
+            state: Mutex::default(),
+        });
+        let mut state = self.state.lock().unwrap();
+        if state.shutdown.is_triggered() {
+            node.state.lock().unwrap().shutdown.trigger();
+        }
+        state.children.push(node.clone());
+        ShutdownToken { node }
+    }
+
+    fn trigger(&self) {
+        let children = {
+            let state = self.state.lock().unwrap();
+            state.shutdown.trigger();
+            state.children.clone()
+        };
+        for child in children {
+            child.trigger();
+        }
+    }
+
+    /// Remove the acknowledged `child`, acknowledging the node itself if it was released and
+    /// it was the last of its children
+    fn acknowledged(&self, child: &Node) {
+        let (wakers, is_acknowledged) = {
+            let mut state = self.state.lock().unwrap();
+            state
+                .children
+                .retain(|other| !core::ptr::eq(&**other, child));
+            let is_acknowledged = state.children.is_empty();
+            let wakers = if is_acknowledged {
+                core::mem::take(&mut state.wakers)
+            } else {
+                Vec::new()
+            };
+            (wakers, is_acknowledged && state.is_released)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+        if is_acknowledged {
+            self.release_from_parent();
+        }
+    }
+
+    fn release_from_parent(&self) {
+        if let Some(parent) = &self.parent {
+            parent.acknowledged(self);
+        }
+    }
+}
+
+impl ShutdownCoordinator {
+    /// Create the root of the tree without children
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the token of a child block
+    pub fn child(&self) -> ShutdownToken {
+        self.node.child()
+    }
+
+    /// Future resolving once [`shutdown`](#method.shutdown) is called
+    pub fn signalled(&self) -> Signalled {
+        self.node.state.lock().unwrap().shutdown.signalled()
+    }
+
+    /// Returns `true` if the shutdown is started
+    pub fn is_triggered(&self) -> bool {
+        self.node.state.lock().unwrap().shutdown.is_triggered()
+    }
+
+    /// Fire the signals of all of the tokens, and wait until all of them acknowledge the
+    /// shutdown
+    pub async fn shutdown(&self) {
+        self.node.trigger();
+        poll_fn(|cx| {
+            let mut state = self.node.state.lock().unwrap();
+            if state.children.is_empty() {
+                return Poll::Ready(());
+            }
+            if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                state.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl ShutdownToken {
+    /// Create the token of a child of this block, which has to acknowledge the shutdown
+    /// before this token does
+    pub fn child(&self) -> ShutdownToken {
+        self.node.child()
+    }
+
+    /// Future resolving once the shutdown of the parent is started
+    pub fn signalled(&self) -> Signalled {
+        self.node.state.lock().unwrap().shutdown.signalled()
+    }
+
+    /// Returns `true` if the shutdown of the parent is started
+    pub fn is_triggered(&self) -> bool {
+        self.node.state.lock().unwrap().shutdown.is_triggered()
+    }
+}
+
+impl Drop for ShutdownToken {
+    fn drop(&mut self) {
+        let is_acknowledged = {
+            let mut state = self.node.state.lock().unwrap();
+            state.is_released = true;
+            state.children.is_empty()
+        };
+        if is_acknowledged {
+            self.node.release_from_parent();
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+
+use crate::LinkedBlock;
+
+/// Future returned by [`LinkedBlock::critical`](struct.LinkedBlock.html#method.critical),
+/// which only terminates when a critical future completes
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Critical<F, C, R> {
+    block: LinkedBlock<F>,
+    is_critical: C,
+    on_non_critical: R,
+}
+
+impl<F, I, T> LinkedBlock<F>
+where
+    F: Future<Output = (I, T)>,
+{
+    /// Only terminate the block when one of the futures for which `is_critical` returns
+    /// `true` completes. The outputs of the rest of the futures are passed to
+    /// `on_non_critical` as they complete, and the futures are removed from the block.
+    ///
+    /// Resolves with `None` if all of the futures complete without a critical one.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block};
+    ///
+    /// linked_block!(Service, ServiceFutureIdentifier; Main, Warmup, Metrics);
+    ///
+    /// let block = link_futures!(Service, ServiceFutureIdentifier;
+    ///     Main => async { "done" },
+    ///     Warmup => async { "warm" },
+    ///     Metrics => pending()
+    /// );
+    /// let completed = block_on(block.critical(
+    ///     |id| *id == ServiceFutureIdentifier::Main,
+    ///     |id, output| println!("{} completed with {}", id, output),
+    /// ));
+    /// assert_eq!(completed, Some((ServiceFutureIdentifier::Main, "done")));
+    /// ```
+    pub fn critical<C, R>(self, is_critical: C, on_non_critical: R) -> Critical<F, C, R>
+    where
+        C: FnMut(&I) -> bool,
+        R: FnMut(I, T),
+    {
+        Critical {
+            block: self,
+            is_critical,
+            on_non_critical,
+        }
+    }
+}
+
+// The linked futures are pinned by `FuturesUnordered` itself, and the closures are never pinned
+impl<F, C, R> Unpin for Critical<F, C, R> {}
+
+impl<F, I, T, C, R> Future for Critical<F, C, R>
+where
+    F: Future<Output = (I, T)>,
+    C: FnMut(&I) -> bool,
+    R: FnMut(I, T),
+{
+    type Output = Option<(I, T)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Critical {
+            block,
+            is_critical,
+            on_non_critical,
+        } = &mut *self;
+
+        block.poll_until(cx, |identifier, output| {
+            if is_critical(&identifier) {
+                Some((identifier, output))
+            } else {
+                on_non_critical(identifier, output);
+                None
+            }
+        })
+    }
+}
+
+impl<F, I, T, C, R> FusedFuture for Critical<F, C, R>
+where
+    F: Future<Output = (I, T)>,
+    C: FnMut(&I) -> bool,
+    R: FnMut(I, T),
+{
+    fn is_terminated(&self) -> bool {
+        self.block.is_terminated()
+    }
+}
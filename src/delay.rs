@@ -0,0 +1,37 @@
+use core::future::Future;
+use core::ops::Range;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::Timer;
+
+/// Delay of the first poll of a future linked with `#[delay]`: either a fixed duration, or
+/// a range of durations to pick a random one from
+#[doc(hidden)]
+pub trait StartDelay {
+    fn duration(self) -> Duration;
+}
+
+impl StartDelay for Duration {
+    fn duration(self) -> Duration {
+        self
+    }
+}
+
+impl StartDelay for Range<Duration> {
+    fn duration(self) -> Duration {
+        if self.start >= self.end {
+            return self.start;
+        }
+        // `RandomState` is seeded randomly, which is good enough for spreading the starts
+        let random = RandomState::new().build_hasher().finish();
+        let span = (self.end - self.start).as_nanos();
+        self.start + Duration::from_nanos((u128::from(random) % span) as u64)
+    }
+}
+
+pub(crate) async fn delay<T: Timer, F: Future>(future: F, delay: impl StartDelay) -> F::Output {
+    T::delay_for(delay.duration()).await;
+    future.await
+}
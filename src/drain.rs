@@ -0,0 +1,199 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+
+use crate::{LinkedBlock, Shutdown};
+
+/// Output of the block returned by [`LinkedBlock::drain`](struct.LinkedBlock.html#method.drain)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drained<I, T> {
+    /// Identifier of the future which terminated the block
+    pub id: I,
+    /// Output of the future which terminated the block
+    pub output: T,
+    /// Outputs of the futures which completed during the grace period, in the order
+    /// of completion
+    pub drained: Vec<(I, T)>,
+    /// Number of the futures which were still running when the grace period elapsed
+    pub remaining: usize,
+}
+
+/// Future returned by [`LinkedBlock::drain`](struct.LinkedBlock.html#method.drain), which
+/// keeps polling the rest of the futures for a grace period after the first one completes
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Drain<F, I, T, G, D> {
+    futures: FuturesUnordered<F>,
+    grace: Option<G>,
+    delay: Option<D>,
+    terminated_by: Option<(I, T)>,
+    drained: Vec<(I, T)>,
+    shutdown: Option<Shutdown>,
+    is_terminated: bool,
+}
+
+impl<F, I, T> LinkedBlock<F>
+where
+    F: Future<Output = (I, T)>,
+{
+    /// Once the first future completes, keep polling the rest of them until they all
+    /// complete, or until the future returned by `grace` resolves. `grace` is called when
+    /// the first future completes, and usually creates a timer of the runtime, such as
+    /// `tokio::time::delay_for`.
+    ///
+    /// Skipped and detached futures never complete, so the block waits for the whole grace
+    /// period if there are any.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use futures::future::pending;
+    /// use tokio::time::delay_for;
+    ///
+    /// use linked_futures::{link_futures, linked_block};
+    ///
+    /// linked_block!(Service, ServiceFutureIdentifier; Stop, Flush, Listen);
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let block = link_futures!(Service, ServiceFutureIdentifier;
+    ///         Stop => async {},
+    ///         Flush => async { delay_for(Duration::from_millis(1)).await },
+    ///         Listen => pending()
+    ///     );
+    ///     let drained = block.drain(|| delay_for(Duration::from_millis(100))).await;
+    ///     assert_eq!(drained.id, ServiceFutureIdentifier::Stop);
+    ///     assert_eq!(drained.drained, [(ServiceFutureIdentifier::Flush, ())]);
+    ///     assert_eq!(drained.remaining, 1);
+    /// }
+    /// ```
+    pub fn drain<G, D>(self, grace: G) -> Drain<F, I, T, G, D>
+    where
+        G: FnOnce() -> D,
+        D: Future<Output = ()>,
+    {
+        Drain {
+            futures: self.into_inner(),
+            grace: Some(grace),
+            delay: None,
+            terminated_by: None,
+            drained: Vec::new(),
+            shutdown: None,
+            is_terminated: false,
+        }
+    }
+
+    /// Shut the block down gracefully: once the first future completes, trigger `shutdown`,
+    /// and [`drain`](#method.drain) the rest of the futures, so they may observe the signal
+    /// and finish cleanly within the grace period. Triggering `shutdown` from outside of the
+    /// block stops it the same way, once the first future finishes.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block, Shutdown};
+    ///
+    /// linked_block!(Service, ServiceFutureIdentifier; Stop, Writer);
+    ///
+    /// let shutdown = Shutdown::new();
+    /// let signalled = shutdown.signalled();
+    /// let block = link_futures!(Service, ServiceFutureIdentifier;
+    ///     Stop => async { "stopped" },
+    ///     Writer => async {
+    ///         signalled.await;
+    ///         "flushed"
+    ///     }
+    /// );
+    /// let drained = block_on(block.graceful(&shutdown, || pending()));
+    /// assert_eq!(drained.id, ServiceFutureIdentifier::Stop);
+    /// assert_eq!(drained.drained, [(ServiceFutureIdentifier::Writer, "flushed")]);
+    /// ```
+    pub fn graceful<G, D>(self, shutdown: &Shutdown, grace: G) -> Drain<F, I, T, G, D>
+    where
+        G: FnOnce() -> D,
+        D: Future<Output = ()>,
+    {
+        Drain {
+            shutdown: Some(shutdown.clone()),
+            ..self.drain(grace)
+        }
+    }
+}
+
+impl<F, I, T, G, D> Drain<F, I, T, G, D> {
+    /// Outputs of the futures which completed during the grace period so far
+    pub fn drained(&self) -> &[(I, T)] {
+        &self.drained
+    }
+}
+
+impl<F, I, T, G, D> Future for Drain<F, I, T, G, D>
+where
+    F: Future<Output = (I, T)>,
+    G: FnOnce() -> D,
+    D: Future<Output = ()>,
+{
+    type Output = Drained<I, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The delay is structurally pinned, and never moved out once created. The rest of
+        // the fields are never pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        assert!(!this.is_terminated, "Drain polled after completion");
+
+        while this.terminated_by.is_none() {
+            match this.futures.poll_next_unpin(cx) {
+                Poll::Ready(Some(output)) => {
+                    this.terminated_by = Some(output);
+                    if let Some(shutdown) = &this.shutdown {
+                        shutdown.trigger();
+                    }
+                }
+                // None of the futures is left to terminate the block
+                Poll::Ready(None) => return Poll::Pending,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        loop {
+            match this.futures.poll_next_unpin(cx) {
+                Poll::Ready(Some(output)) => this.drained.push(output),
+                Poll::Ready(None) => break,
+                Poll::Pending => {
+                    if let Some(grace) = this.grace.take() {
+                        this.delay = Some(grace());
+                    }
+                    let delay = this.delay.as_mut().expect("grace period is started");
+                    match unsafe { Pin::new_unchecked(delay) }.poll(cx) {
+                        Poll::Ready(()) => break,
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+            }
+        }
+
+        this.is_terminated = true;
+        let (id, output) = this.terminated_by.take().expect("block is terminated");
+        Poll::Ready(Drained {
+            id,
+            output,
+            drained: core::mem::take(&mut this.drained),
+            remaining: this.futures.len(),
+        })
+    }
+}
+
+impl<F, I, T, G, D> FusedFuture for Drain<F, I, T, G, D>
+where
+    F: Future<Output = (I, T)>,
+    G: FnOnce() -> D,
+    D: Future<Output = ()>,
+{
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
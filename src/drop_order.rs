@@ -0,0 +1,153 @@
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+
+use crate::LinkedBlock;
+
+/// Order in which [`LinkedBlock::drop_order`](struct.LinkedBlock.html#method.drop_order) drops
+/// the futures which are still running when the block is dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DropOrder {
+    /// Drop the futures in the order they are declared in the linking macro
+    Declaration,
+    /// Drop the futures in the reverse order they are declared in the linking macro
+    Reverse,
+}
+
+/// Linked future, which remembers its position in the linking macro, so it can be dropped
+/// in place in a deterministic order
+#[doc(hidden)]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Declared<F> {
+    index: usize,
+    future: Option<F>,
+}
+
+impl<F> Declared<F> {
+    pub fn new(index: usize, future: F) -> Self {
+        Declared {
+            index,
+            future: Some(future),
+        }
+    }
+
+    fn release(self: Pin<&mut Self>) {
+        // The future is dropped in place, without being moved out
+        unsafe { self.get_unchecked_mut() }.future = None;
+    }
+}
+
+impl<F: Future> Future for Declared<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The future is structurally pinned, and only dropped in place
+        let future = unsafe { self.get_unchecked_mut() }
+            .future
+            .as_mut()
+            .expect("Declared polled after it's dropped");
+        unsafe { Pin::new_unchecked(future) }.poll(cx)
+    }
+}
+
+/// Future returned by [`LinkedBlock::drop_order`](struct.LinkedBlock.html#method.drop_order),
+/// which drops the remaining futures in the chosen order
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Ordered<F> {
+    block: LinkedBlock<Declared<F>>,
+    order: DropOrder,
+}
+
+impl<F, I, T> LinkedBlock<Declared<F>>
+where
+    F: Future<Output = (I, T)>,
+{
+    /// Drop the futures which are still running when the block is dropped in the declaration
+    /// order, or in the reverse declaration order, instead of the unspecified order of the
+    /// underlying `FuturesUnordered`. Useful when the futures hold resources which must be
+    /// released in a certain order, such as a consumer which must be dropped before its
+    /// producer.
+    ///
+    /// Blocks returned by [`link_biased_futures`](macro.link_biased_futures.html) always drop
+    /// the futures in the declaration order.
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block, DropOrder};
+    ///
+    /// linked_block!(Pipeline, PipelineFutureIdentifier; Producer, Consumer, Stop);
+    ///
+    /// struct Dropped(&'static str, Arc<Mutex<Vec<&'static str>>>);
+    ///
+    /// impl Drop for Dropped {
+    ///     fn drop(&mut self) {
+    ///         self.1.lock().unwrap().push(self.0);
+    ///     }
+    /// }
+    ///
+    /// let dropped = Arc::new(Mutex::new(Vec::new()));
+    /// let producer = Dropped("producer", dropped.clone());
+    /// let consumer = Dropped("consumer", dropped.clone());
+    /// let block = link_futures!(Pipeline, PipelineFutureIdentifier;
+    ///     Producer => async move {
+    ///         let _producer = producer;
+    ///         pending::<()>().await
+    ///     },
+    ///     Consumer => async move {
+    ///         let _consumer = consumer;
+    ///         pending::<()>().await
+    ///     },
+    ///     Stop => async {}
+    /// );
+    /// let (id, ()) = block_on(block.drop_order(DropOrder::Reverse));
+    /// assert_eq!(id, PipelineFutureIdentifier::Stop);
+    /// assert_eq!(*dropped.lock().unwrap(), ["consumer", "producer"]);
+    /// ```
+    pub fn drop_order(self, order: DropOrder) -> Ordered<F> {
+        Ordered { block: self, order }
+    }
+}
+
+impl<F, I, T> Future for Ordered<F>
+where
+    F: Future<Output = (I, T)>,
+{
+    type Output = (I, T);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.block).poll(cx)
+    }
+}
+
+impl<F, I, T> FusedFuture for Ordered<F>
+where
+    F: Future<Output = (I, T)>,
+{
+    fn is_terminated(&self) -> bool {
+        self.block.is_terminated()
+    }
+}
+
+impl<F> Drop for Ordered<F> {
+    fn drop(&mut self) {
+        let mut futures = Pin::new(self.block.futures_mut())
+            .iter_pin_mut()
+            .collect::<Vec<_>>();
+        futures.sort_by_key(|future| future.index);
+        if self.order == DropOrder::Reverse {
+            futures.reverse();
+        }
+        for future in futures {
+            future.release();
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::any::Any;
+use core::convert::Infallible;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::io;
+
+use futures::future::Aborted;
+
+/// Error returned when parsing an identifier enum from a string which doesn't match
+/// any of the variant names
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseIdentifierError {
+    name: String,
+}
+
+impl ParseIdentifierError {
+    #[doc(hidden)]
+    pub fn new(name: &str) -> Self {
+        ParseIdentifierError {
+            name: name.to_owned(),
+        }
+    }
+
+    /// The string which failed to parse
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for ParseIdentifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown linked future `{}`", self.name)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ParseIdentifierError {}
+
+/// Error returned when a linked block, or a linked future with a timeout, doesn't complete
+/// before its timeout
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TimedOut;
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("linked block timed out")
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for TimedOut {}
+
+/// Error returned when a linked block is cancelled before any of the linked futures
+/// complete
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("linked block cancelled")
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for Cancelled {}
+
+#[cfg(feature = "std")]
+impl From<TimedOut> for io::Error {
+    fn from(err: TimedOut) -> Self {
+        io::Error::new(io::ErrorKind::TimedOut, err)
+    }
+}
+
+/// Outcome of a block returned by
+/// [`LinkedBlock::catch_unwind`](struct.LinkedBlock.html#method.catch_unwind), when a linked
+/// future marked with `#[catch_unwind]` panics. Holds the identifier of the future, and the
+/// payload of the panic.
+pub struct Panicked<I>(pub I, pub Box<dyn Any + Send + 'static>);
+
+impl<I: fmt::Debug> fmt::Debug for Panicked<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Panicked")
+            .field(&self.0)
+            .field(&"Any { .. }")
+            .finish()
+    }
+}
+
+impl<I: fmt::Display> fmt::Display for Panicked<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "linked future `{}` panicked", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I: fmt::Debug + fmt::Display> Error for Panicked<I> {}
+
+/// Error completing a linked future marked with `#[restart(max = restarts)]`, once it's
+/// restarted more times than allowed
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Escalated<I>(pub I);
+
+impl<I: fmt::Display> fmt::Display for Escalated<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "linked future `{}` restarted too many times", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I: fmt::Debug + fmt::Display> Error for Escalated<I> {}
+
+/// Error of a linked future, paired with the identifier of the future. The linking macros
+/// called with `identified_error = T` convert the errors of the futures into `T` through it,
+/// so catch-all error types, such as `anyhow::Error`, hold the identifier as the context of
+/// the error, which is the source of `IdentifiedError`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct IdentifiedError<I, E>(pub I, pub E);
+
+impl<I: fmt::Display, E> fmt::Display for IdentifiedError<I, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "linked future `{}` failed", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I, E> Error for IdentifiedError<I, E>
+where
+    I: fmt::Debug + fmt::Display,
+    E: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.1)
+    }
+}
+
+/// Classification of the errors of linked futures marked with `#[restart(severity)]`
+///
+/// The marked futures are restarted after recoverable errors, while fatal errors complete
+/// them, terminating the block:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, Severity};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum PollError {
+///     Unavailable,
+///     Unauthorized,
+/// }
+///
+/// impl Severity for PollError {
+///     fn is_fatal(&self) -> bool {
+///         *self == PollError::Unauthorized
+///     }
+/// }
+///
+/// linked_block!(Block, BlockFutureIdentifier; Poller, Stop);
+///
+/// let mut polls = 0;
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     #[restart(severity)]
+///     Poller => || {
+///         polls += 1;
+///         let polled = polls;
+///         async move {
+///             match polled {
+///                 1 => Ok(()),
+///                 2 => Err(PollError::Unavailable),
+///                 _ => Err(PollError::Unauthorized),
+///             }
+///         }
+///     },
+///     Stop => pending()
+/// );
+/// let (completed_future_identifier, result) = block_on(block);
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Poller);
+/// assert_eq!(result, Err::<(), _>(PollError::Unauthorized));
+/// assert_eq!(polls, 3);
+/// ```
+///
+/// Errors which don't implement the trait may be classified by a closure instead, with
+/// `#[restart(fatal = |err| ...)]`.
+pub trait Severity {
+    /// Returns `true` if the error is fatal, and `false` if it's recoverable
+    fn is_fatal(&self) -> bool;
+}
+
+/// Error of a block returned by
+/// [`LinkedBlockExt::expect_completed`](trait.LinkedBlockExt.html#method.expect_completed),
+/// when it's terminated by another future than the expected one
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UnexpectedCompletion<I, T> {
+    /// Identifier of the future which terminated the block
+    pub id: I,
+    /// Output of the future which terminated the block
+    pub output: T,
+}
+
+impl<I: fmt::Display, T> fmt::Display for UnexpectedCompletion<I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "linked future `{}` completed unexpectedly", self.id)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I: fmt::Debug + fmt::Display, T: fmt::Debug> Error for UnexpectedCompletion<I, T> {}
+
+/// Termination cause of linked blocks, covering the errors of all of the adapters, so that
+/// they may be handled uniformly
+///
+/// The errors are converted with `From`, so they may be propagated with `?`:
+/// ```rust
+/// use futures::channel::oneshot;
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, LinkedBlockExt, LinkedError};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Writer);
+///
+/// type Error = LinkedError<BlockFutureIdentifier>;
+///
+/// async fn run(cancelled: oneshot::Receiver<()>) -> Result<(), Error> {
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         #[catch_unwind]
+///         Reader => pending::<()>(),
+///         Writer => pending()
+///     );
+///     block.catch_unwind().cancel_on(cancelled).await??;
+///     Ok(())
+/// }
+///
+/// let (cancel, cancelled) = oneshot::channel();
+/// cancel.send(()).unwrap();
+/// assert!(matches!(block_on(run(cancelled)), Err(LinkedError::Cancelled)));
+/// ```
+pub enum LinkedError<I, E = Infallible> {
+    /// The block timed out
+    TimedOut,
+    /// The block was cancelled
+    Cancelled,
+    /// The block was aborted
+    Aborted,
+    /// The linked future panicked, with the payload of the panic
+    Panicked(I, Box<dyn Any + Send + 'static>),
+    /// The linked future was restarted too many times
+    Escalated(I),
+    /// The cleanup hook of the linked future failed
+    Cleanup(I, E),
+}
+
+impl<I, E> LinkedError<I, E> {
+    /// Identifier of the linked future which caused the error, if any
+    pub fn id(&self) -> Option<&I> {
+        match self {
+            LinkedError::Panicked(id, _)
+            | LinkedError::Escalated(id)
+            | LinkedError::Cleanup(id, _) => Some(id),
+            LinkedError::TimedOut | LinkedError::Cancelled | LinkedError::Aborted => None,
+        }
+    }
+}
+
+impl<I: fmt::Debug, E: fmt::Debug> fmt::Debug for LinkedError<I, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkedError::TimedOut => f.write_str("TimedOut"),
+            LinkedError::Cancelled => f.write_str("Cancelled"),
+            LinkedError::Aborted => f.write_str("Aborted"),
+            LinkedError::Panicked(id, _) => f
+                .debug_tuple("Panicked")
+                .field(id)
+                .field(&"Any { .. }")
+                .finish(),
+            LinkedError::Escalated(id) => f.debug_tuple("Escalated").field(id).finish(),
+            LinkedError::Cleanup(id, err) => f.debug_tuple("Cleanup").field(id).field(err).finish(),
+        }
+    }
+}
+
+impl<I: fmt::Display, E: fmt::Display> fmt::Display for LinkedError<I, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkedError::TimedOut => fmt::Display::fmt(&TimedOut, f),
+            LinkedError::Cancelled => fmt::Display::fmt(&Cancelled, f),
+            LinkedError::Aborted => f.write_str("linked block aborted"),
+            LinkedError::Panicked(id, _) => write!(f, "linked future `{}` panicked", id),
+            LinkedError::Escalated(id) => fmt::Display::fmt(&Escalated(id), f),
+            LinkedError::Cleanup(id, err) => {
+                write!(f, "cleanup of linked future `{}` failed: {}", id, err)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I, E> Error for LinkedError<I, E>
+where
+    I: fmt::Debug + fmt::Display,
+    E: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LinkedError::Cleanup(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<I, E> From<TimedOut> for LinkedError<I, E> {
+    fn from(_: TimedOut) -> Self {
+        LinkedError::TimedOut
+    }
+}
+
+impl<I, E> From<Cancelled> for LinkedError<I, E> {
+    fn from(_: Cancelled) -> Self {
+        LinkedError::Cancelled
+    }
+}
+
+impl<I, E> From<Aborted> for LinkedError<I, E> {
+    fn from(_: Aborted) -> Self {
+        LinkedError::Aborted
+    }
+}
+
+impl<I, E> From<Panicked<I>> for LinkedError<I, E> {
+    fn from(Panicked(id, payload): Panicked<I>) -> Self {
+        LinkedError::Panicked(id, payload)
+    }
+}
+
+impl<I, E> From<Escalated<I>> for LinkedError<I, E> {
+    fn from(Escalated(id): Escalated<I>) -> Self {
+        LinkedError::Escalated(id)
+    }
+}
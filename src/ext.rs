@@ -0,0 +1,404 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use futures::future::{self, AbortHandle, Abortable, FusedFuture, FutureExt, Inspect, Map};
+
+#[cfg(feature = "tokio")]
+use crate::DefaultTimer;
+use crate::{Cancelled, TimedOut, UnexpectedCompletion};
+#[cfg(feature = "std")]
+use crate::{OnIdle, Timer};
+
+/// Adapters for the blocks returned by [`link_futures`](macro.link_futures.html) and
+/// the other linking macros, implemented for every future
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use futures::future::pending;
+/// use tokio::time::delay_for;
+///
+/// use linked_futures::{link_futures, linked_block, LinkedBlockExt, TimedOut};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Writer);
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         Reader => pending::<()>(),
+///         Writer => pending::<()>()
+///     );
+///     let result = block
+///         .inspect_completed(|(id, _)| println!("{} completed", id))
+///         .map_completed(|(id, _)| id)
+///         .with_timeout(delay_for(Duration::from_millis(10)))
+///         .await;
+///     assert_eq!(result, Err(TimedOut));
+/// }
+/// ```
+pub trait LinkedBlockExt: Future {
+    /// Map the output of the block
+    fn map_completed<U, G>(self, f: G) -> Map<Self, G>
+    where
+        Self: Sized,
+        G: FnOnce(Self::Output) -> U,
+    {
+        FutureExt::map(self, f)
+    }
+
+    /// Run a side effect with the output of the block, once it completes
+    fn inspect_completed<G>(self, f: G) -> Inspect<Self, G>
+    where
+        Self: Sized,
+        G: FnOnce(&Self::Output),
+    {
+        FutureExt::inspect(self, f)
+    }
+
+    /// Resolve with [`TimedOut`](struct.TimedOut.html) if the block doesn't complete before
+    /// the `delay` future, usually a timer of the runtime, such as `tokio::time::delay_for`
+    fn with_timeout<D>(self, delay: D) -> WithTimeout<Self, D>
+    where
+        Self: Sized,
+        D: Future<Output = ()>,
+    {
+        WithTimeout::new(self, delay)
+    }
+
+    /// Resolve with [`Cancelled`](struct.Cancelled.html) once the `cancel` future completes,
+    /// unless the block completes first. Any future signalling the cancellation may be used,
+    /// such as `CancellationToken::cancelled` of `tokio-util`, or a receiver of a channel.
+    ///
+    /// ```rust
+    /// use futures::channel::oneshot;
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block, Cancelled, LinkedBlockExt};
+    ///
+    /// linked_block!(Block, BlockFutureIdentifier; Reader, Writer);
+    ///
+    /// let (cancel, cancelled) = oneshot::channel::<()>();
+    /// let block = link_futures!(Block, BlockFutureIdentifier;
+    ///     Reader => pending::<()>(),
+    ///     Writer => pending::<()>()
+    /// );
+    /// let block = block.cancel_on(cancelled);
+    /// cancel.send(()).unwrap();
+    /// assert_eq!(block_on(block), Err(Cancelled));
+    /// ```
+    fn cancel_on<C: Future>(self, cancel: C) -> CancelOn<Self, C>
+    where
+        Self: Sized,
+    {
+        CancelOn {
+            block: self,
+            cancel,
+            is_terminated: false,
+        }
+    }
+
+    /// Make the block abortable from another task with the returned handle. The block
+    /// resolves with `Err(Aborted)` once aborted.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block, Aborted, LinkedBlockExt};
+    ///
+    /// linked_block!(Block, BlockFutureIdentifier; Reader, Writer);
+    ///
+    /// let (block, abort_handle) = link_futures!(Block, BlockFutureIdentifier;
+    ///     Reader => pending::<()>(),
+    ///     Writer => pending::<()>()
+    /// )
+    /// .abortable();
+    /// std::thread::spawn(move || abort_handle.abort());
+    /// assert_eq!(block_on(block), Err(Aborted));
+    /// ```
+    fn abortable(self) -> (Abortable<Self>, AbortHandle)
+    where
+        Self: Sized,
+    {
+        future::abortable(self)
+    }
+
+    /// Resolve with `Ok` holding the output of the `success` future once it terminates the
+    /// block, and with [`UnexpectedCompletion`](struct.UnexpectedCompletion.html) if another
+    /// future terminates it, so the outcome may be propagated with `?`.
+    ///
+    /// ```rust
+    /// use std::error::Error;
+    ///
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block, LinkedBlockExt};
+    ///
+    /// linked_block!(Service, ServiceFutureIdentifier; Listen, Stop);
+    ///
+    /// async fn run() -> Result<&'static str, Box<dyn Error>> {
+    ///     let block = link_futures!(Service, ServiceFutureIdentifier;
+    ///         Listen => async { "listener closed" },
+    ///         Stop => pending()
+    ///     );
+    ///     let reason = block.expect_completed(ServiceFutureIdentifier::Stop).await?;
+    ///     Ok(reason)
+    /// }
+    ///
+    /// let err = block_on(run()).unwrap_err();
+    /// assert_eq!(err.to_string(), "linked future `Listen` completed unexpectedly");
+    /// ```
+    fn expect_completed<I, T>(self, success: I) -> ExpectCompleted<Self, I>
+    where
+        Self: Future<Output = (I, T)> + Sized,
+        I: PartialEq,
+    {
+        ExpectCompleted {
+            block: self,
+            success,
+            is_terminated: false,
+        }
+    }
+
+    /// Resolve with [`TimedOut`](struct.TimedOut.html) if the block doesn't complete by
+    /// `deadline`, using the [`DefaultTimer`](type.DefaultTimer.html)
+    ///
+    /// ```rust
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block, LinkedBlockExt, TimedOut};
+    ///
+    /// linked_block!(Block, BlockFutureIdentifier; Reader, Writer);
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let block = link_futures!(Block, BlockFutureIdentifier;
+    ///         Reader => pending::<()>(),
+    ///         Writer => pending::<()>()
+    ///     );
+    ///     let deadline = Instant::now() + Duration::from_millis(10);
+    ///     assert_eq!(block.with_deadline(deadline).await, Err(TimedOut));
+    /// }
+    /// ```
+    #[cfg(feature = "tokio")]
+    fn with_deadline(self, deadline: Instant) -> WithTimeout<Self, <DefaultTimer as Timer>::Delay>
+    where
+        Self: Sized,
+    {
+        self.with_deadline_on::<DefaultTimer>(deadline)
+    }
+
+    /// Resolve with [`TimedOut`](struct.TimedOut.html) if the block doesn't complete within
+    /// `duration`, using the [`DefaultTimer`](type.DefaultTimer.html)
+    #[cfg(feature = "tokio")]
+    fn with_timeout_after(
+        self,
+        duration: Duration,
+    ) -> WithTimeout<Self, <DefaultTimer as Timer>::Delay>
+    where
+        Self: Sized,
+    {
+        self.with_timeout(DefaultTimer::delay_for(duration))
+    }
+
+    /// Resolve with [`TimedOut`](struct.TimedOut.html) if the block doesn't complete by
+    /// `deadline`, using the timer `T`
+    #[cfg(feature = "std")]
+    fn with_deadline_on<T: Timer>(self, deadline: Instant) -> WithTimeout<Self, T::Delay>
+    where
+        Self: Sized,
+    {
+        self.with_timeout(T::delay_until(deadline))
+    }
+
+    /// Call `on_idle` whenever none of the linked futures are woken for `threshold`, with
+    /// the time since the last wake, checking with the timer `T`. While the block stays
+    /// idle, `on_idle` is called again after every `threshold`.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block, LinkedBlockExt};
+    ///
+    /// linked_block!(Block, BlockFutureIdentifier; Consumer, Stop);
+    ///
+    /// # #[cfg(feature = "tokio")]
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut stalls = 0;
+    ///     let block = link_futures!(Block, BlockFutureIdentifier;
+    ///         Consumer => tokio::time::delay_for(Duration::from_millis(50)),
+    ///         Stop => pending()
+    ///     );
+    ///     block
+    ///         .on_idle::<linked_futures::DefaultTimer, _>(Duration::from_millis(10), |idle| {
+    ///             println!("the block is stalled for {:?}", idle);
+    ///             stalls += 1;
+    ///         })
+    ///         .await;
+    ///     assert!(stalls >= 1);
+    /// }
+    /// # #[cfg(not(feature = "tokio"))]
+    /// # fn main() {}
+    /// ```
+    #[cfg(feature = "std")]
+    fn on_idle<T, C>(self, threshold: Duration, on_idle: C) -> OnIdle<Self, T, C>
+    where
+        Self: Sized,
+        T: Timer,
+        C: FnMut(Duration),
+    {
+        OnIdle::new(self, threshold, on_idle)
+    }
+}
+
+impl<F: Future + ?Sized> LinkedBlockExt for F {}
+
+/// Future returned by [`LinkedBlockExt::with_timeout`](trait.LinkedBlockExt.html#method.with_timeout)
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WithTimeout<B, D> {
+    block: B,
+    delay: D,
+    is_terminated: bool,
+}
+
+impl<B, D> WithTimeout<B, D> {
+    pub(crate) fn new(block: B, delay: D) -> Self {
+        WithTimeout {
+            block,
+            delay,
+            is_terminated: false,
+        }
+    }
+}
+
+impl<B, D> Future for WithTimeout<B, D>
+where
+    B: Future,
+    D: Future<Output = ()>,
+{
+    type Output = Result<B::Output, TimedOut>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The block and the delay are structurally pinned, and never moved out
+        let this = unsafe { self.get_unchecked_mut() };
+        assert!(!this.is_terminated, "WithTimeout polled after completion");
+
+        if let Poll::Ready(output) = unsafe { Pin::new_unchecked(&mut this.block) }.poll(cx) {
+            this.is_terminated = true;
+            return Poll::Ready(Ok(output));
+        }
+        if let Poll::Ready(()) = unsafe { Pin::new_unchecked(&mut this.delay) }.poll(cx) {
+            this.is_terminated = true;
+            return Poll::Ready(Err(TimedOut));
+        }
+        Poll::Pending
+    }
+}
+
+impl<B, D> FusedFuture for WithTimeout<B, D>
+where
+    B: Future,
+    D: Future<Output = ()>,
+{
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
+
+/// Future returned by [`LinkedBlockExt::cancel_on`](trait.LinkedBlockExt.html#method.cancel_on)
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct CancelOn<B, C> {
+    block: B,
+    cancel: C,
+    is_terminated: bool,
+}
+
+impl<B: Future, C: Future> Future for CancelOn<B, C> {
+    type Output = Result<B::Output, Cancelled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The block and the cancel future are structurally pinned, and never moved out
+        let this = unsafe { self.get_unchecked_mut() };
+        assert!(!this.is_terminated, "CancelOn polled after completion");
+
+        if let Poll::Ready(output) = unsafe { Pin::new_unchecked(&mut this.block) }.poll(cx) {
+            this.is_terminated = true;
+            return Poll::Ready(Ok(output));
+        }
+        if unsafe { Pin::new_unchecked(&mut this.cancel) }
+            .poll(cx)
+            .is_ready()
+        {
+            this.is_terminated = true;
+            return Poll::Ready(Err(Cancelled));
+        }
+        Poll::Pending
+    }
+}
+
+impl<B: Future, C: Future> FusedFuture for CancelOn<B, C> {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
+
+/// Future returned by
+/// [`LinkedBlockExt::expect_completed`](trait.LinkedBlockExt.html#method.expect_completed)
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ExpectCompleted<B, I> {
+    block: B,
+    success: I,
+    is_terminated: bool,
+}
+
+impl<B, I, T> Future for ExpectCompleted<B, I>
+where
+    B: Future<Output = (I, T)>,
+    I: PartialEq,
+{
+    type Output = Result<T, UnexpectedCompletion<I, T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The block is structurally pinned, and never moved out
+        let this = unsafe { self.get_unchecked_mut() };
+        assert!(
+            !this.is_terminated,
+            "ExpectCompleted polled after completion"
+        );
+
+        match unsafe { Pin::new_unchecked(&mut this.block) }.poll(cx) {
+            Poll::Ready((id, output)) => {
+                this.is_terminated = true;
+                if id == this.success {
+                    Poll::Ready(Ok(output))
+                } else {
+                    Poll::Ready(Err(UnexpectedCompletion { id, output }))
+                }
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<B, I, T> FusedFuture for ExpectCompleted<B, I>
+where
+    B: Future<Output = (I, T)>,
+    I: PartialEq,
+{
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
@@ -0,0 +1,21 @@
+/// Definition of a block returned by [`link_factories`](macro.link_factories.html), which
+/// links new futures built by the factories every time the block is rebuilt
+#[derive(Debug, Clone)]
+pub struct LinkedFactory<C> {
+    build: C,
+}
+
+impl<C, B> LinkedFactory<C>
+where
+    C: Fn() -> B,
+{
+    #[doc(hidden)]
+    pub fn new(build: C) -> Self {
+        LinkedFactory { build }
+    }
+
+    /// Build a new block, calling all of the factories
+    pub fn rebuild(&self) -> B {
+        (self.build)()
+    }
+}
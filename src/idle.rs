@@ -0,0 +1,121 @@
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::FusedFuture;
+use futures::task::{waker, ArcWake, AtomicWaker};
+
+use crate::Timer;
+
+/// Future returned by [`LinkedBlockExt::on_idle`](trait.LinkedBlockExt.html#method.on_idle),
+/// which reports the periods when none of the linked futures are woken
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct OnIdle<B, T: Timer, C> {
+    block: B,
+    delay: Option<T::Delay>,
+    threshold: Duration,
+    on_idle: C,
+    active_at: Instant,
+    activity: Arc<Activity>,
+    is_terminated: bool,
+    timer: PhantomData<T>,
+}
+
+impl<B, T: Timer, C> core::fmt::Debug for OnIdle<B, T, C>
+where
+    B: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OnIdle")
+            .field("block", &self.block)
+            .field("threshold", &self.threshold)
+            .field("active_at", &self.active_at)
+            .finish()
+    }
+}
+
+/// Waker of the block, which records that one of the linked futures was woken
+struct Activity {
+    is_woken: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl ArcWake for Activity {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.is_woken.store(true, Ordering::Release);
+        arc_self.waker.wake();
+    }
+}
+
+impl<B, T: Timer, C> OnIdle<B, T, C> {
+    pub(crate) fn new(block: B, threshold: Duration, on_idle: C) -> Self {
+        OnIdle {
+            block,
+            delay: None,
+            threshold,
+            on_idle,
+            active_at: Instant::now(),
+            activity: Arc::new(Activity {
+                is_woken: AtomicBool::new(true),
+                waker: AtomicWaker::new(),
+            }),
+            is_terminated: false,
+            timer: PhantomData,
+        }
+    }
+}
+
+impl<B, T, C> Future for OnIdle<B, T, C>
+where
+    B: Future,
+    T: Timer,
+    C: FnMut(Duration),
+{
+    type Output = B::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The block and the delay are structurally pinned. The delay is only replaced
+        // in place, and the rest of the fields are never pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        assert!(!this.is_terminated, "OnIdle polled after completion");
+
+        this.activity.waker.register(cx.waker());
+        if this.activity.is_woken.swap(false, Ordering::AcqRel) {
+            this.active_at = Instant::now();
+            let block_waker = waker(this.activity.clone());
+            let mut block_cx = Context::from_waker(&block_waker);
+            if let Poll::Ready(output) =
+                unsafe { Pin::new_unchecked(&mut this.block) }.poll(&mut block_cx)
+            {
+                this.is_terminated = true;
+                return Poll::Ready(output);
+            }
+            this.delay = Some(T::delay_until(this.active_at + this.threshold));
+        }
+
+        let delay = this.delay.as_mut().expect("the block is polled");
+        if let Poll::Ready(()) = unsafe { Pin::new_unchecked(delay) }.poll(cx) {
+            (this.on_idle)(this.active_at.elapsed());
+            this.delay = Some(T::delay_for(this.threshold));
+            // Poll the next delay on the next poll, so a timer resolving immediately
+            // doesn't starve the executor
+            cx.waker().wake_by_ref();
+        }
+        Poll::Pending
+    }
+}
+
+impl<B, T, C> FusedFuture for OnIdle<B, T, C>
+where
+    B: Future,
+    T: Timer,
+    C: FnMut(Duration),
+{
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
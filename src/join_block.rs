@@ -0,0 +1,101 @@
+use alloc::collections::BTreeMap;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+
+/// Future returned by [`join_futures`](macro.join_futures.html), which resolves with the
+/// outputs of all of the futures, or of the quorum of them, keyed by their identifiers
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LinkedJoinBlock<F, I, T> {
+    futures: FuturesUnordered<F>,
+    outputs: BTreeMap<I, T>,
+    quorum: Option<usize>,
+    is_terminated: bool,
+}
+
+impl<F, I, T> LinkedJoinBlock<F, I, T>
+where
+    F: Future<Output = Option<(I, T)>>,
+    I: Ord,
+{
+    /// Link the futures into a block
+    pub fn new(futures: FuturesUnordered<F>) -> Self {
+        LinkedJoinBlock {
+            futures,
+            outputs: BTreeMap::new(),
+            quorum: None,
+            is_terminated: false,
+        }
+    }
+
+    /// Link the futures into a block, which completes once `quorum` of the futures complete
+    pub fn with_quorum(futures: FuturesUnordered<F>, quorum: usize) -> Self {
+        LinkedJoinBlock {
+            quorum: Some(quorum),
+            ..LinkedJoinBlock::new(futures)
+        }
+    }
+}
+
+impl<F, I, T> LinkedJoinBlock<F, I, T> {
+    /// Outputs of the futures which have already completed
+    pub fn outputs(&self) -> &BTreeMap<I, T> {
+        &self.outputs
+    }
+
+    /// Consume the block, returning the futures which are not yet completed
+    pub fn into_inner(self) -> FuturesUnordered<F> {
+        self.futures
+    }
+}
+
+// The linked futures are pinned by `FuturesUnordered` itself, and the outputs are never pinned
+impl<F, I, T> Unpin for LinkedJoinBlock<F, I, T> {}
+
+impl<F, I, T> Future for LinkedJoinBlock<F, I, T>
+where
+    F: Future<Output = Option<(I, T)>>,
+    I: Ord,
+{
+    type Output = BTreeMap<I, T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        assert!(
+            !self.is_terminated,
+            "LinkedJoinBlock polled after completion"
+        );
+
+        loop {
+            if Some(self.outputs.len()) == self.quorum {
+                self.is_terminated = true;
+                return Poll::Ready(core::mem::take(&mut self.outputs));
+            }
+
+            match self.futures.poll_next_unpin(cx) {
+                Poll::Ready(Some(Some((identifier, output)))) => {
+                    self.outputs.insert(identifier, output);
+                }
+                Poll::Ready(Some(None)) => {}
+                Poll::Ready(None) => {
+                    self.is_terminated = true;
+                    return Poll::Ready(core::mem::take(&mut self.outputs));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<F, I, T> FusedFuture for LinkedJoinBlock<F, I, T>
+where
+    F: Future<Output = Option<(I, T)>>,
+    I: Ord,
+{
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
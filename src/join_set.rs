@@ -0,0 +1,115 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::collections::HashMap;
+
+use futures::future::FusedFuture;
+use tokio1::task::{Id, JoinError, JoinSet};
+
+/// Block of linked futures spawned into a tokio [`JoinSet`], which resolves with the
+/// identifier of the first completed task, paired with its result
+///
+/// Works like [`LinkedTaskBlock`](struct.LinkedTaskBlock.html), but the tasks are owned by the
+/// `JoinSet` of tokio 1, so they are isolated from each other, reported by the metrics of the
+/// runtime, and a panic or a cancellation of a task resolves the block with the
+/// [`JoinError`] of the task instead of being resumed. Once a task completes, the rest of them
+/// are aborted, and dropping the block aborts all of the running tasks. Requires the
+/// `join-set` feature. A block without tasks never resolves.
+/// ```rust
+/// use futures::future::pending;
+///
+/// use linked_futures::LinkedJoinSet;
+///
+/// let runtime = tokio1::runtime::Builder::new_current_thread().build().unwrap();
+/// runtime.block_on(async {
+///     let mut block = LinkedJoinSet::new();
+///     block.spawn("reader", pending::<&str>());
+///     block.spawn("writer", async { panic!("disk is full") });
+///     let (completed_identifier, result) = block.await;
+///     assert_eq!(completed_identifier, "writer");
+///     assert!(result.unwrap_err().is_panic());
+/// });
+/// ```
+///
+/// [`JoinSet`]: https://docs.rs/tokio/1/tokio/task/struct.JoinSet.html
+/// [`JoinError`]: https://docs.rs/tokio/1/tokio/task/struct.JoinError.html
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LinkedJoinSet<I, T> {
+    tasks: JoinSet<T>,
+    identifiers: HashMap<Id, I>,
+    is_terminated: bool,
+}
+
+impl<I, T: 'static> Default for LinkedJoinSet<I, T> {
+    fn default() -> Self {
+        LinkedJoinSet {
+            tasks: JoinSet::new(),
+            identifiers: HashMap::new(),
+            is_terminated: false,
+        }
+    }
+}
+
+impl<I, T: 'static> LinkedJoinSet<I, T> {
+    /// Create the block without tasks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of the tasks which are not yet completed
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Returns `true` if there are no tasks in the block
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Abort all of the running tasks, without waiting until they are cancelled
+    pub fn abort(&mut self) {
+        self.tasks.abort_all();
+    }
+}
+
+impl<I, T: Send + 'static> LinkedJoinSet<I, T> {
+    /// Spawn `future` tagged with `identifier` into the `JoinSet` of the block. Must be called
+    /// from the context of a tokio 1 runtime.
+    pub fn spawn<F>(&mut self, identifier: I, future: F)
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        let handle = self.tasks.spawn(future);
+        self.identifiers.insert(handle.id(), identifier);
+    }
+}
+
+impl<I: Unpin, T: 'static> Future for LinkedJoinSet<I, T> {
+    type Output = (I, Result<T, JoinError>);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        assert!(!self.is_terminated, "LinkedJoinSet polled after completion");
+
+        let (id, result) = match self.tasks.poll_join_next_with_id(cx) {
+            Poll::Ready(Some(Ok((id, output)))) => (id, Ok(output)),
+            Poll::Ready(Some(Err(err))) => (err.id(), Err(err)),
+            // None of the tasks is left to terminate the block
+            Poll::Ready(None) => return Poll::Pending,
+            Poll::Pending => return Poll::Pending,
+        };
+        let identifier = self
+            .identifiers
+            .remove(&id)
+            .expect("task is spawned by the block");
+        self.is_terminated = true;
+        self.abort();
+        Poll::Ready((identifier, result))
+    }
+}
+
+impl<I: Unpin, T: 'static> FusedFuture for LinkedJoinSet<I, T> {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
@@ -1,109 +1,3468 @@
 #![doc(html_root_url = "https://docs.rs/linked-futures/0.1.3")]
 #![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
-#![deny(intra_doc_link_resolution_failure)]
+#![deny(rustdoc::broken_intra_doc_links)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 //! This crate provides the way to "link" futures into a single block,
 //! which stops executing once any of these futures complete.
 //!
 //! Under the hood, it uses [`FuturesUnordered`](https://docs.rs/futures/0.3.1/futures/stream/struct.FuturesUnordered.html)
-//! to execute multiple futures efficiently. In order to avoid boxing, custom `one-of` type from
-//! [`one-of-futures`](https://crates.io/crates/one-of-futures) crate is generated for
-//! each [`link_futures`](macro.link_futures.html) block.
+//! to execute multiple futures efficiently. In order to avoid boxing, custom `one-of` type, like
+//! the ones from [`one-of-futures`](https://crates.io/crates/one-of-futures) crate, is generated
+//! for each [`link_futures`](macro.link_futures.html) block.
+//!
+//! For blocks which are only used in one place, [`linked`](macro.linked.html) declares
+//! and links the futures in a single step.
+//!
+//! Futures with different outputs are linked with [`link_typed_futures`](macro.link_typed_futures.html),
+//! which resolves with an output enum carrying the value of the completed future.
+//!
+//! With the `macros` feature enabled, procedural versions of the macros, which report errors
+//! at the offending variant or future expression, are available in [`macros`](macros/index.html).
+//!
+//! With the `tokio` feature enabled, blocks may be given a deadline with
+//! [`LinkedBlockExt::with_deadline`](trait.LinkedBlockExt.html#method.with_deadline), which
+//! resolves them with [`TimedOut`](struct.TimedOut.html) once exceeded. The time-based
+//! features go through the [`Timer`](trait.Timer.html) trait, so timers of other runtimes
+//! may be plugged in. With the `futures-timer` feature enabled, the
+//! [`FuturesTimer`](struct.FuturesTimer.html) of `futures-timer` doesn't depend on a runtime.
+//!
+//! With the `wasm` feature enabled, the blocks run on `wasm32-unknown-unknown` with the
+//! [`WasmSpawner`](struct.WasmSpawner.html) of `wasm-bindgen-futures` and the
+//! [`GlooTimer`](struct.GlooTimer.html) of `gloo-timers`.
+//!
+//! With the `tracing` feature enabled, every linked future runs within a `linked_future` span
+//! of `tracing`, carrying the names of the block and of the variant, which reports when the
+//! future is started, completed, or cancelled.
+//!
+//! With the `metrics` feature enabled, every linked future records the metrics of `metrics`,
+//! labelled with the `block` and the `variant` named by `as_str`: the counter
+//! `linked_future_terminations_total` of the completions of the future, which terminate the
+//! block for the blocks resolving with the first completed future, the histogram
+//! `linked_future_duration_seconds` of the time it ran for, with the `outcome` label being
+//! either `completed` or `cancelled`, and the counter `linked_future_restarts_total` of the
+//! restarts of the variants marked with `#[restart]`, or restarted after a panic by
+//! [`PanicPolicy::Restart`](enum.PanicPolicy.html#variant.Restart).
+//!
+//! With the `executor` feature enabled, [`run_linked`](fn.run_linked.html) drives a block to
+//! completion on the current thread, without a runtime.
+//!
+//! The `std` feature is enabled by default. Without it, the crate is `no_std` and only
+//! requires `alloc`: the linking macros and the blocks polling the linked futures, such as
+//! [`LinkedBlock`](struct.LinkedBlock.html), are available, while the features relying on
+//! time, threads, panics or the std collections, including the linking features using them,
+//! require `std`.
+
+extern crate alloc;
 
-pub use futures::stream::{FuturesUnordered, StreamExt};
+#[cfg(all(feature = "tokio", not(feature = "std")))]
+compile_error!("the `tokio` feature requires the `std` feature");
+
+#[cfg(feature = "std")]
+pub use abort::AbortHandles;
+pub use all_ready::AllReady;
+pub use biased_block::LinkedBiasedBlock;
+pub use block::LinkedBlock;
+pub use builder::LinkedBlockBuilder;
+#[cfg(feature = "std")]
+pub use catch_unwind::{CatchUnwind, PanicPolicy};
+#[cfg(feature = "std")]
+pub use circuit_breaker::{CircuitBreaker, CircuitState};
+pub use completions::Completions;
+#[cfg(feature = "std")]
+pub use coordinator::{ShutdownCoordinator, ShutdownToken};
+pub use critical::Critical;
+#[cfg(feature = "std")]
+pub use drain::{Drain, Drained};
+pub use drop_order::{DropOrder, Ordered};
+pub use error::{
+    Cancelled, Escalated, IdentifiedError, LinkedError, Panicked, ParseIdentifierError, Severity,
+    TimedOut, UnexpectedCompletion,
+};
+pub use ext::{CancelOn, ExpectCompleted, LinkedBlockExt, WithTimeout};
+pub use factory::LinkedFactory;
+pub use futures::future::{AbortHandle, Abortable, Aborted, FusedFuture};
+pub use futures::stream::{FusedStream, FuturesUnordered, Stream, StreamExt};
+#[cfg(feature = "std")]
+pub use idle::OnIdle;
+pub use join_block::LinkedJoinBlock;
+#[cfg(feature = "join-set")]
+pub use join_set::LinkedJoinSet;
+#[cfg(feature = "std")]
+pub use linked_futures::{BoxedLinkedFutures, Handle, KeyedCompletions, LinkedFutures, WithMeta};
+#[cfg(feature = "std")]
+pub use linked_streams::{LinkedStreams, StreamEvent};
+#[cfg(feature = "std")]
+pub use linked_thread::{LinkedThread, ThreadExited, ThreadStop};
+#[cfg(feature = "std")]
+pub use on_cancel::{CancelHooks, CleanupReport, HookOutput, OnCancel};
+#[cfg(feature = "std")]
 pub use one_of_futures::impl_one_of;
+pub use parts::Identified;
+pub use remaining::Remaining;
+#[cfg(feature = "std")]
+pub use retry::{Backoff, Exhausted};
+#[cfg(feature = "executor")]
+pub use runner::run_linked;
+pub use select_ok_block::LinkedSelectOkBlock;
+#[cfg(feature = "std")]
+pub use shutdown::{Shutdown, Signalled};
+#[cfg(feature = "signals")]
+pub use signal::{termination_signal, Signal};
+#[cfg(feature = "async-std")]
+pub use spawner::AsyncStdSpawner;
+#[cfg(feature = "smol")]
+pub use spawner::SmolSpawner;
+#[cfg(feature = "wasm")]
+pub use spawner::WasmSpawner;
+#[cfg(feature = "tokio")]
+pub use spawner::{DefaultSpawner, TokioSpawner};
+#[cfg(feature = "std")]
+pub use spawner::{LocalSpawner, Spawner};
+#[cfg(feature = "std")]
+pub use startup::Startup;
+#[cfg(feature = "std")]
+pub use task_block::LinkedTaskBlock;
+#[cfg(feature = "std")]
+pub use timed::{Terminated, Timed, Uptime};
+#[cfg(feature = "futures-timer")]
+pub use timer::FuturesTimer;
+#[cfg(feature = "wasm")]
+pub use timer::GlooTimer;
+#[cfg(feature = "tokio")]
+pub use timer::{DefaultTimer, TokioTimer};
+#[cfg(feature = "std")]
+pub use timer::{ThreadDelay, ThreadTimer, Timer};
+pub use try_block::LinkedTryBlock;
+pub use until::Until;
+#[cfg(feature = "std")]
+pub use watchdog::{Watchdog, Watched};
 
-/// Create necessary enums for later usage with [`link_futures`](macro.link_futures.html)
-#[macro_export]
-macro_rules! linked_block {
-    ( $one_of_block:ident, $identifier_enum:ident; $($variants:ident),* ) => {
-        $crate::impl_one_of!($one_of_block; $($variants),*);
+#[cfg(feature = "std")]
+mod abort;
+mod all_ready;
+mod biased_block;
+mod block;
+mod builder;
+#[cfg(feature = "std")]
+mod catch_unwind;
+#[cfg(feature = "std")]
+mod circuit_breaker;
+mod completions;
+#[cfg(feature = "std")]
+mod coordinator;
+mod critical;
+#[cfg(feature = "std")]
+mod delay;
+#[cfg(feature = "std")]
+mod drain;
+mod drop_order;
+mod error;
+mod ext;
+mod factory;
+#[cfg(feature = "std")]
+mod idle;
+mod join_block;
+#[cfg(feature = "join-set")]
+mod join_set;
+#[cfg(feature = "std")]
+mod linked_futures;
+#[cfg(feature = "std")]
+mod linked_streams;
+#[cfg(feature = "std")]
+mod linked_thread;
+#[cfg(feature = "metrics")]
+mod metered;
+#[cfg(feature = "std")]
+mod on_cancel;
+mod parts;
+mod remaining;
+#[cfg(feature = "std")]
+mod retry;
+#[cfg(feature = "executor")]
+mod runner;
+mod select_ok_block;
+#[cfg(feature = "serde")]
+mod serde_identifier;
+#[cfg(feature = "std")]
+mod shutdown;
+#[cfg(feature = "signals")]
+mod signal;
+#[cfg(feature = "std")]
+mod spawner;
+#[cfg(feature = "std")]
+mod startup;
+#[cfg(feature = "std")]
+mod task_block;
+#[cfg(feature = "std")]
+mod timed;
+#[cfg(feature = "std")]
+mod timer;
+#[cfg(feature = "tracing")]
+mod traced;
+mod try_block;
+mod until;
+#[cfg(feature = "std")]
+mod watchdog;
 
-        #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
-        enum $identifier_enum {
-            $($variants),*
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec::Vec;
+    pub use futures::future::{pending, TryFutureExt};
+
+    pub use crate::drop_order::Declared;
+
+    #[cfg(feature = "serde")]
+    pub use crate::serde_identifier::{deserialize_identifier, serialize_identifier};
+    #[cfg(feature = "serde")]
+    pub use serde;
+
+    pub trait Completion {
+        type Identifier;
+        type Output;
+
+        fn into_completion(self) -> (Self::Identifier, Self::Output);
+    }
+
+    impl<I, T> Completion for (I, T) {
+        type Identifier = I;
+        type Output = T;
+
+        fn into_completion(self) -> (I, T) {
+            self
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<I, T> Completion for crate::Terminated<I, T> {
+        type Identifier = I;
+        type Output = T;
+
+        fn into_completion(self) -> (I, T) {
+            (self.id, self.output)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub async fn catch_unwind<I, F>(identifier: I, future: F) -> F::Output
+    where
+        I: Send + 'static,
+        F: core::future::Future,
+    {
+        use futures::future::FutureExt;
+
+        match std::panic::AssertUnwindSafe(future).catch_unwind().await {
+            Ok(output) => output,
+            Err(payload) => {
+                std::panic::resume_unwind(Box::new(crate::Panicked(identifier, payload)))
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub async fn on_panic<I, F, R>(
+        identifier: I,
+        policy: crate::PanicPolicy,
+        mut factory: F,
+    ) -> R::Output
+    where
+        I: Send + 'static,
+        F: FnMut() -> R,
+        R: core::future::Future,
+    {
+        use futures::future::FutureExt;
+
+        loop {
+            match std::panic::AssertUnwindSafe(factory()).catch_unwind().await {
+                Ok(output) => return output,
+                Err(payload) => match policy {
+                    crate::PanicPolicy::Propagate => std::panic::resume_unwind(payload),
+                    crate::PanicPolicy::Abort => std::process::abort(),
+                    crate::PanicPolicy::Complete => {
+                        std::panic::resume_unwind(Box::new(crate::Panicked(identifier, payload)))
+                    }
+                    crate::PanicPolicy::Restart => restarting().await,
+                },
+            }
+        }
+    }
+
+    pub async fn restart<F, R>(mut factory: F)
+    where
+        F: FnMut() -> R,
+        R: core::future::Future,
+    {
+        loop {
+            factory().await;
+            restarting().await;
+        }
+    }
+
+    pub async fn identify_err<C, I, F, T, E>(identifier: I, future: F) -> Result<T, C>
+    where
+        F: core::future::Future<Output = Result<T, E>>,
+        crate::IdentifiedError<I, E>: Into<C>,
+    {
+        future
+            .await
+            .map_err(|err| crate::IdentifiedError(identifier, err).into())
+    }
+
+    pub fn is_fatal<E: crate::Severity>(err: &E) -> bool {
+        err.is_fatal()
+    }
+
+    pub async fn restart_unless_fatal<C, F, R, T, E, O>(classify: C, mut factory: F) -> Result<O, E>
+    where
+        C: Fn(&E) -> bool,
+        F: FnMut() -> R,
+        R: core::future::Future<Output = Result<T, E>>,
+    {
+        loop {
+            if let Err(err) = factory().await {
+                if classify(&err) {
+                    return Err(err);
+                }
+            }
+            restarting().await;
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub async fn supervise<I, F, R, T>(
+        identifier: I,
+        max: usize,
+        window: Option<std::time::Duration>,
+        mut factory: F,
+    ) -> Result<T, crate::Escalated<I>>
+    where
+        F: FnMut() -> R,
+        R: core::future::Future,
+    {
+        let mut restarts = std::collections::VecDeque::new();
+        loop {
+            factory().await;
+            let now = std::time::Instant::now();
+            if let Some(window) = window {
+                while restarts.front().is_some_and(|at| now - *at > window) {
+                    restarts.pop_front();
+                }
+            }
+            if restarts.len() >= max {
+                return Err(crate::Escalated(identifier));
+            }
+            restarts.push_back(now);
+            restarting().await;
+        }
+    }
+
+    async fn restarting() {
+        #[cfg(feature = "metrics")]
+        crate::metered::restarted();
+        YieldNow(false).await
+    }
+
+    struct YieldNow(bool);
+
+    impl core::future::Future for YieldNow {
+        type Output = ();
+
+        fn poll(
+            mut self: core::pin::Pin<&mut Self>,
+            cx: &mut core::task::Context<'_>,
+        ) -> core::task::Poll<()> {
+            if self.0 {
+                return core::task::Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            core::task::Poll::Pending
+        }
+    }
+
+    pub use core::marker::PhantomData;
+
+    #[cfg(feature = "std")]
+    pub use crate::delay::StartDelay;
+    #[cfg(feature = "tokio")]
+    pub use crate::DefaultSpawner;
+    #[cfg(feature = "tokio")]
+    pub use crate::DefaultTimer;
+    #[cfg(feature = "std")]
+    use crate::Timer;
+
+    #[cfg(feature = "std")]
+    pub async fn delay<C: Timer, F: core::future::Future>(
+        _timer: PhantomData<C>,
+        future: F,
+        delay: impl StartDelay,
+    ) -> F::Output {
+        crate::delay::delay::<C, _>(future, delay).await
+    }
+
+    #[cfg(feature = "std")]
+    pub fn within<C: Timer, F: core::future::Future>(
+        _timer: PhantomData<C>,
+        future: F,
+        duration: std::time::Duration,
+    ) -> crate::WithTimeout<F, C::Delay> {
+        crate::WithTimeout::new(future, C::delay_for(duration))
+    }
+
+    #[cfg(feature = "std")]
+    pub async fn try_within<C, F, T, E>(
+        timer: PhantomData<C>,
+        future: F,
+        duration: std::time::Duration,
+    ) -> Result<T, E>
+    where
+        C: Timer,
+        F: core::future::Future<Output = Result<T, E>>,
+        E: From<crate::TimedOut>,
+    {
+        match within(timer, future, duration).await {
+            Ok(result) => result,
+            Err(err) => Err(err.into()),
         }
     }
+
+    #[cfg(feature = "std")]
+    pub async fn retry<C: Timer, F, R, T, E>(
+        _timer: PhantomData<C>,
+        max: u32,
+        backoff: crate::Backoff,
+        mut factory: F,
+    ) -> Result<T, crate::Exhausted<E>>
+    where
+        F: FnMut() -> R,
+        R: core::future::Future<Output = Result<T, E>>,
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match factory().await {
+                Ok(output) => return Ok(output),
+                Err(error) if attempts > max => return Err(crate::Exhausted { error, attempts }),
+                Err(_) => C::delay_for(backoff.delay(attempts - 1)).await,
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub async fn circuit_breaker<C: Timer, F, R, T, E>(
+        _timer: PhantomData<C>,
+        mut breaker: crate::CircuitBreaker,
+        mut factory: F,
+    ) where
+        F: FnMut() -> R,
+        R: core::future::Future<Output = Result<T, E>>,
+    {
+        use crate::CircuitState;
+
+        let mut state = CircuitState::Closed;
+        let mut failures = 0;
+        loop {
+            let is_failed = factory().await.is_err();
+            failures = if is_failed { failures + 1 } else { 0 };
+            let next = match state {
+                CircuitState::Closed if failures >= breaker.failures() => CircuitState::Open,
+                CircuitState::Closed => CircuitState::Closed,
+                CircuitState::HalfOpen if is_failed => CircuitState::Open,
+                CircuitState::HalfOpen | CircuitState::Open => CircuitState::Closed,
+            };
+            if next != state {
+                breaker.transition(next);
+                state = next;
+            }
+            if state == CircuitState::Open {
+                C::delay_for(breaker.cool_down()).await;
+                state = CircuitState::HalfOpen;
+                breaker.transition(state);
+            } else {
+                YieldNow(false).await;
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub async fn every<C: Timer, F, R>(
+        _timer: PhantomData<C>,
+        period: std::time::Duration,
+        mut tick: F,
+    ) where
+        F: FnMut(u64) -> R,
+        R: core::future::Future,
+    {
+        let mut deadline = std::time::Instant::now();
+        for number in 0.. {
+            C::delay_until(deadline).await;
+            tick(number).await;
+            deadline += period;
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub async fn schedule<C: Timer, S, F, R>(_timer: PhantomData<C>, schedule: S, mut run: F)
+    where
+        S: IntoIterator<Item = std::time::Instant>,
+        F: FnMut(std::time::Instant) -> R,
+        R: core::future::Future,
+    {
+        for instant in schedule {
+            C::delay_until(instant).await;
+            run(instant).await;
+        }
+        pending::<()>().await
+    }
+
+    #[cfg(feature = "std")]
+    pub fn startup<C, S, F>(
+        _timer: PhantomData<C>,
+        start: S,
+        duration: std::time::Duration,
+    ) -> crate::startup::StartupTimeout<F, C::Delay>
+    where
+        C: Timer,
+        S: FnOnce(crate::Startup) -> F,
+        F: core::future::Future,
+    {
+        crate::startup::StartupTimeout::new::<C>(duration, start)
+    }
+
+    #[cfg(feature = "std")]
+    pub async fn try_startup<C, S, F, T, E>(
+        timer: PhantomData<C>,
+        start: S,
+        duration: std::time::Duration,
+    ) -> Result<T, E>
+    where
+        C: Timer,
+        S: FnOnce(crate::Startup) -> F,
+        F: core::future::Future<Output = Result<T, E>>,
+        E: From<crate::TimedOut>,
+    {
+        match startup(timer, start, duration).await {
+            Ok(result) => result,
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn optional<F: core::future::Future>(future: Option<F>) -> F::Output {
+        match future {
+            Some(future) => future.await,
+            None => pending().await,
+        }
+    }
+
+    pub fn build<F: core::future::Future>(factory: impl FnOnce() -> F) -> F {
+        factory()
+    }
+
+    #[cfg(feature = "std")]
+    pub async fn blocking<S: crate::Spawner, C, T>(_spawner: PhantomData<S>, call: C) -> T
+    where
+        C: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        S::spawn_blocking(move || {
+            let _ = sender.send(std::panic::catch_unwind(std::panic::AssertUnwindSafe(call)));
+        });
+        match receiver.await {
+            Ok(Ok(output)) => output,
+            Ok(Err(panic)) => std::panic::resume_unwind(panic),
+            Err(_) => panic!("blocking task is dropped without completing"),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn spawn<S: crate::Spawner, F>(
+        _spawner: PhantomData<S>,
+        futures: Vec<F>,
+    ) -> crate::LinkedTaskBlock<F::Output>
+    where
+        F: core::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let mut block = crate::LinkedTaskBlock::new();
+        for future in futures {
+            block.spawn_on::<S, _>(future);
+        }
+        block
+    }
+
+    #[cfg(feature = "std")]
+    pub fn spawn_local<S: crate::LocalSpawner, F>(
+        _spawner: PhantomData<S>,
+        futures: Vec<F>,
+    ) -> crate::LinkedTaskBlock<F::Output>
+    where
+        F: core::future::Future + 'static,
+    {
+        let mut block = crate::LinkedTaskBlock::new();
+        for future in futures {
+            block.spawn_local_on::<S, _>(future);
+        }
+        block
+    }
+
+    pub fn instrument<F: core::future::Future>(
+        block: &'static str,
+        variant: &'static str,
+        future: F,
+    ) -> impl core::future::Future<Output = F::Output> {
+        #[cfg(feature = "metrics")]
+        let future = crate::metered::Metered::new(block, variant, future);
+        #[cfg(feature = "tracing")]
+        let future = crate::traced::Traced::new(block, variant, future);
+        #[cfg(not(any(feature = "metrics", feature = "tracing")))]
+        let _ = (block, variant);
+        future
+    }
+
+    pub fn call<T, R>(value: T, handler: impl FnOnce(T) -> R) -> R {
+        handler(value)
+    }
+
+    pub fn assert_same_output<F1, F2>(_: &F1, _: &F2)
+    where
+        F1: core::future::Future,
+        F2: core::future::Future<Output = F1::Output>,
+    {
+    }
 }
 
-/// Link multiple futures into a single block
+/// Procedural versions of the [`linked_block`](../macro.linked_block.html) and
+/// [`linked`](../macro.linked.html) macros, which report errors at the offending
+/// variant or future expression
 ///
 /// Example:
 /// ```rust
-/// use std::time::Duration;
+/// use futures::executor::block_on;
+/// use futures::future::pending;
 ///
-/// use futures::{pin_mut, SinkExt, StreamExt};
-/// use futures::channel::mpsc;
+/// use linked_futures::link_futures;
+/// use linked_futures::macros::{linked, linked_block};
+///
+/// #[linked_block(SimpleBlock)]
+/// enum SimpleBlockFutureIdentifier {
+///     Never,
+///     Stop,
+/// }
+///
+/// block_on(async {
+///     let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+///         Never => pending::<()>(),
+///         Stop => async {}
+///     );
+///     assert_eq!(block.await.0, SimpleBlockFutureIdentifier::Stop);
+///
+///     linked!(let block: InlineFutureIdentifier;
+///         Never => pending::<()>(),
+///         Stop => async {}
+///     );
+///     assert_eq!(block.await.0, InlineFutureIdentifier::Stop);
+/// });
+/// ```
+#[cfg(feature = "macros")]
+pub mod macros {
+    pub use linked_futures_macros::{linked, linked_block};
+}
+
+/// Create necessary enums for later usage with [`link_futures`](macro.link_futures.html)
+///
+/// The enums are private by default. An optional visibility may be specified in front of
+/// the block name; it is applied to both of the generated enums:
+/// ```rust
+/// mod blocks {
+///     linked_futures::linked_block!(pub(crate) SharedBlock, SharedBlockFutureIdentifier;
+///         Reader,
+///         Stop
+///     );
+/// }
+///
+/// use blocks::{SharedBlock, SharedBlockFutureIdentifier};
+///
+/// let block = linked_futures::link_futures!(SharedBlock, SharedBlockFutureIdentifier;
+///     Reader => futures::future::pending::<()>(),
+///     Stop => async {}
+/// );
+/// let (completed_future_identifier, _) = futures::executor::block_on(block);
+/// assert_eq!(completed_future_identifier, SharedBlockFutureIdentifier::Stop);
+/// ```
+///
+/// Outer attributes and doc comments are accepted in front of each enum name and each
+/// variant. Attributes in front of the block name are applied to the one-of enum, the ones
+/// in front of the identifier name and the variants are applied to the identifier enum:
+/// ```rust
+/// linked_futures::linked_block!(
+///     /// Futures of the periodic sender
+///     PeriodicSender,
+///     /// Identifies the future which stopped the periodic sender
+///     #[derive(Default)]
+///     PeriodicSenderFutureIdentifier;
+///     /// Sends the instants
+///     Generator,
+///     /// Stops the sender
+///     #[default]
+///     Stop
+/// );
+///
+/// assert_eq!(PeriodicSenderFutureIdentifier::default(), PeriodicSenderFutureIdentifier::Stop);
+/// ```
+///
+/// The identifier enum always derives `Copy`, `Clone`, `PartialEq`, `Eq`, `PartialOrd`, `Ord`,
+/// `Hash` and `Debug`, and implements `Display` and `FromStr` using the variant names,
+/// which are also returned by `as_str()`.
+/// With the `serde` feature enabled, the identifier enum also implements `Serialize` and
+/// `Deserialize` as an enum of unit variants. Additional derives, like `strum::EnumIter`, are
+/// appended with a `#[derive]` attribute in front of the identifier name, the same way as
+/// `Default` in the example above.
+///
+/// All of the identifiers are available in the `ALL`, `COUNT` and `iter()` associated items:
+/// ```rust
+/// linked_futures::linked_block!(Block, BlockFutureIdentifier; Reader, Writer, Stop);
+///
+/// assert_eq!(BlockFutureIdentifier::COUNT, 3);
+/// assert_eq!(BlockFutureIdentifier::ALL[1], BlockFutureIdentifier::Writer);
+/// let names = BlockFutureIdentifier::iter().map(|id| id.to_string()).collect::<Vec<_>>();
+/// assert_eq!(names, ["Reader", "Writer", "Stop"]);
+/// ```
+///
+/// Identifier enums which are exposed from a library can be marked with `#[non_exhaustive]`,
+/// so futures can be added to the block without breaking the downstream crates which match
+/// on the identifier. The attribute may be applied to the one-of enum as well:
+/// ```rust
+/// linked_futures::linked_block!(
+///     #[non_exhaustive]
+///     pub Block,
+///     #[non_exhaustive]
+///     BlockFutureIdentifier;
+///     Reader,
+///     Stop
+/// );
+/// ```
+///
+/// A variant may be followed by a description of the linked future, which is returned by
+/// `description()`, so logs and dashboards can explain what each future does. Variants
+/// without a description return an empty string:
+/// ```rust
+/// linked_futures::linked_block!(Block, BlockFutureIdentifier;
+///     Reader: "consumes instants from the generator",
+///     Stop
+/// );
+///
+/// assert_eq!(
+///     BlockFutureIdentifier::Reader.description(),
+///     "consumes instants from the generator"
+/// );
+/// assert_eq!(BlockFutureIdentifier::Stop.description(), "");
+/// ```
+///
+/// The position of the identifier is returned by `index()`, and converted back with
+/// `from_index()`. Adding `#[repr(u8)]` in front of the identifier name makes the enum
+/// a single byte, with the position as its value:
+/// ```rust
+/// linked_futures::linked_block!(Block, #[repr(u8)] BlockFutureIdentifier; Reader, Writer, Stop);
+///
+/// assert_eq!(std::mem::size_of::<BlockFutureIdentifier>(), 1);
+/// assert_eq!(BlockFutureIdentifier::Stop.index(), 2);
+/// assert_eq!(BlockFutureIdentifier::Stop as u8, 2);
+/// assert_eq!(BlockFutureIdentifier::from_index(1), Some(BlockFutureIdentifier::Writer));
+/// assert_eq!(BlockFutureIdentifier::from_index(3), None);
+/// ```
+///
+/// The one-of enum is generic over the type of each linked future, and the type parameters
+/// are named after the variants. Additional generic parameters may be declared after the
+/// block name and constrained in a `where` section following the variants. The linked
+/// futures resolve to the identifier paired with the output of the future. Since all of the
+/// futures share the output of the first variant, bounds on the output should only be
+/// placed on the first variant:
+/// ```rust
+/// use std::future::Future;
+///
+/// use futures::channel::oneshot;
 /// use futures::executor::block_on;
-/// use tokio::time::{delay_for, interval, Instant};
+/// use futures::future::pending;
 ///
 /// use linked_futures::{link_futures, linked_block};
 ///
-/// linked_block!(PeriodicStoppableSender, PeriodicStoppableSenderFutureIdentifier;
-///     Forwarder,
+/// linked_block!(Relay<T>, RelayFutureIdentifier;
+///     Receiver,
+///     Stop;
+///     where Receiver: Future<Output = (RelayFutureIdentifier, Option<T>)>
+/// );
+///
+/// async fn relay<T>(rx: oneshot::Receiver<T>) -> Option<T> {
+///     let block = link_futures!(Relay, RelayFutureIdentifier;
+///         Receiver => async { rx.await.ok() },
+///         Stop => pending()
+///     );
+///     block.await.1
+/// }
+///
+/// let (tx, rx) = oneshot::channel();
+/// tx.send(1).unwrap();
+/// assert_eq!(block_on(relay(rx)), Some(1));
+/// ```
+///
+/// The name of an output enum may follow the identifier name. The output enum has a
+/// variant wrapping the output of each future, which is generic over the outputs, and an
+/// `identifier()` method. Blocks linked with [`link_typed_futures`](macro.link_typed_futures.html)
+/// resolve with the output enum, so the futures don't need to share the same output:
+/// ```rust
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_typed_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier, BlockOutput; Reader, Stop);
+///
+/// let block = link_typed_futures!(Block, BlockFutureIdentifier, BlockOutput;
+///     Reader => async { 42 },
+///     Stop => futures::future::pending::<()>()
+/// );
+/// let output = block_on(block);
+/// assert_eq!(output.identifier(), BlockFutureIdentifier::Reader);
+/// assert_eq!(output, BlockOutput::Reader(42));
+/// ```
+///
+/// When all of the futures share the same output, it is split from the identifier with
+/// `into_parts()`, the same way as the result of [`link_futures`](macro.link_futures.html):
+/// ```rust
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_typed_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier, BlockOutput; Reader, Writer);
+///
+/// let block = link_typed_futures!(Block, BlockFutureIdentifier, BlockOutput;
+///     Reader => futures::future::pending::<Result<(), String>>(),
+///     Writer => async { Err("closed".to_string()) }
+/// );
+/// let (completed_future_identifier, result) = block_on(block).into_parts();
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Writer);
+/// assert_eq!(result, Err("closed".to_string()));
+/// ```
+///
+/// Variants may be conditionally compiled with `#[cfg]`. Disabled variants are removed from
+/// both enums, so they are also missing from `ALL`, `COUNT`, `iter()` and `FromStr`:
+/// ```rust
+/// linked_futures::linked_block!(Block, BlockFutureIdentifier;
 ///     Reader,
-///     Generator,
+///     #[cfg(any())]
+///     Metrics,
 ///     Stop
 /// );
 ///
-/// #[tokio::main]
-/// async fn main() {
-///     let (mut tx1, mut rx1) = mpsc::channel::<Instant>(1);
-///     let (mut tx2, mut rx2) = mpsc::channel::<Instant>(1);
+/// assert_eq!(BlockFutureIdentifier::COUNT, 2);
+/// assert!("Metrics".parse::<BlockFutureIdentifier>().is_err());
+/// ```
 ///
-///     let mut interval = interval(Duration::from_millis(100));
+/// `parts = Name` after the enum names generates the parts returned by
+/// [`link_parts`](macro.link_parts.html), a struct with a public `Option` field named after
+/// each variant. A future may be taken out of its field to be driven separately, and the rest
+/// are linked into the block with `link()`. Parts can't be generated for blocks with additional
+/// generic parameters or a `where` section:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::{pending, select, Either};
 ///
-///     let generator = async {
-///         while let Some(instant) = interval.next().await {
-///             tx1.send(instant).await;
-///         }
-///     };
-///     let forwarder = async {
-///         while let Some(instant) = rx1.next().await {
-///             tx2.send(instant).await;
-///         }
-///     };
-///     let reader = async {
-///         while let Some(instant) = rx2.next().await {
-///             println!("instant: {:?}", instant);
-///         }
-///     };
-///     let stop = async {
-///         delay_for(Duration::from_secs(1)).await;
-///     };
-///     let linked = link_futures!(
-///        PeriodicStoppableSender,
-///        PeriodicStoppableSenderFutureIdentifier;
-///        Generator => generator,
-///        Forwarder => forwarder,
-///        Reader => reader,
-///        Stop => stop
-///     );
-///     block_on(async {
-///         pin_mut!(linked);
-///         let (completed_future_identifier, _) = linked.await;
-///         match completed_future_identifier {
-///             PeriodicStoppableSenderFutureIdentifier::Stop =>
-///                 println!("linked block stopped normally"),
-///             n =>
-///                 panic!("linked block unexpectedly terminated by future: {:?}", n),
-///         }
-///     });
+/// use linked_futures::{link_parts, linked_block};
+///
+/// linked_block!(Service, ServiceFutureIdentifier, parts = ServiceParts; Listener, Worker);
+///
+/// let mut parts = link_parts!(ServiceParts, ServiceFutureIdentifier;
+///     Listener => pending::<u32>(),
+///     Worker => async { 42 }
+/// );
+/// let worker = parts.Worker.take().unwrap();
+/// assert_eq!(parts.len(), 1);
+///
+/// match block_on(select(Box::pin(worker), parts.link())) {
+///     Either::Left((output, _)) => assert_eq!(output, 42),
+///     Either::Right(_) => unreachable!(),
 /// }
 /// ```
 #[macro_export]
-macro_rules! link_futures {
-    ( $one_of_block:ident, $identifier_enum:ident; $( $key:ident => $value:expr ),* ) => {{
-        let mut linked = $crate::FuturesUnordered::new();
-        $( linked.push($one_of_block::$key(async {
-            ($identifier_enum::$key, $value.await)
-        })); )*
-        async move {
-            use $crate::StreamExt;
+macro_rules! linked_block {
+    (
+        $(#[$one_of_meta:meta])* $vis:vis $one_of_block:ident $(< $($generic:ident),+ $(,)? >)?,
+        $(#[$identifier_meta:meta])* $identifier_enum:ident, parts = $parts:ident;
+        $($variants:tt)*
+    ) => {
+        $crate::__linked_variants!(
+            [$(#[$one_of_meta])*] [$vis] $one_of_block [$($($generic),+)?]
+            [$(#[$identifier_meta])*] $identifier_enum [[] [$parts]];
+            [] [] [] $($variants)*
+        );
+    };
+    (
+        $(#[$one_of_meta:meta])* $vis:vis $one_of_block:ident $(< $($generic:ident),+ $(,)? >)?,
+        $(#[$identifier_meta:meta])* $identifier_enum:ident,
+        $(#[$output_meta:meta])* $output_enum:ident, parts = $parts:ident;
+        $($variants:tt)*
+    ) => {
+        $crate::__linked_variants!(
+            [$(#[$one_of_meta])*] [$vis] $one_of_block [$($($generic),+)?]
+            [$(#[$identifier_meta])*] $identifier_enum [[$(#[$output_meta])* $output_enum] [$parts]];
+            [] [] [] $($variants)*
+        );
+    };
+    (
+        $(#[$one_of_meta:meta])* $vis:vis $one_of_block:ident $(< $($generic:ident),+ $(,)? >)?,
+        $(#[$identifier_meta:meta])* $identifier_enum:ident
+        $(, $(#[$output_meta:meta])* $output_enum:ident)?;
+        $($variants:tt)*
+    ) => {
+        $crate::__linked_variants!(
+            [$(#[$one_of_meta])*] [$vis] $one_of_block [$($($generic),+)?]
+            [$(#[$identifier_meta])*] $identifier_enum [[$($(#[$output_meta])* $output_enum)?] []];
+            [] [] [] $($variants)*
+        );
+    };
+}
+
+/// Collects the variants of [`linked_block`](macro.linked_block.html), dropping the ones
+/// disabled by `#[cfg]` before the block is generated
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linked_variants {
+    (
+        [$($one_of_meta:tt)*] [$vis:vis] $one_of_block:ident [$($generic:ident),*]
+        [$($identifier_meta:tt)*] $identifier_enum:ident [[$($output:tt)*] [$($parts:tt)*]];
+        [$( [$($variant_meta:tt)*] $variants:ident [$($description:literal)?] )*] [] []
+        $(; where $($where_clause:tt)*)?
+    ) => {
+        $crate::__linked_unique!(@item ($) ["variant" "is declared more than once"] [
+            $crate::__linked_one_of!(
+                $($one_of_meta)* $vis $one_of_block [$($generic),*] [$($($where_clause)*)?];
+                $($variants),*
+            );
+
+            $crate::__linked_identifier!(
+                $($identifier_meta)* $vis $identifier_enum;
+                $( $($variant_meta)* $variants $(: $description)? ),*
+            );
+
+            $crate::__linked_output!([$($output)*] $vis $identifier_enum; $($variants),*);
 
-            linked.next().await.unwrap()
+            $crate::__linked_parts!(
+                [$($parts)*] [$($generic),*] [$($($where_clause)*)?] $vis $one_of_block $identifier_enum;
+                $($variants),*
+            );
+        ] $($variants)*);
+    };
+    (
+        $block:tt $vis:tt $one_of_block:ident $generics:tt $identifier_meta:tt $identifier_enum:ident $output:tt;
+        $variants:tt [] [] , $($rest:tt)*
+    ) => {
+        $crate::__linked_variants!(
+            $block $vis $one_of_block $generics $identifier_meta $identifier_enum $output;
+            $variants [] [] $($rest)*
+        );
+    };
+    (
+        $block:tt $vis:tt $one_of_block:ident $generics:tt $identifier_meta:tt $identifier_enum:ident $output:tt;
+        $variants:tt [$($cfg:meta),*] [$($variant_meta:tt)*] #[cfg($predicate:meta)] $($rest:tt)*
+    ) => {
+        $crate::__linked_variants!(
+            $block $vis $one_of_block $generics $identifier_meta $identifier_enum $output;
+            $variants [$($cfg,)* $predicate] [$($variant_meta)*] $($rest)*
+        );
+    };
+    (
+        $block:tt $vis:tt $one_of_block:ident $generics:tt $identifier_meta:tt $identifier_enum:ident $output:tt;
+        $variants:tt $cfg:tt [$($variant_meta:tt)*] #[$meta:meta] $($rest:tt)*
+    ) => {
+        $crate::__linked_variants!(
+            $block $vis $one_of_block $generics $identifier_meta $identifier_enum $output;
+            $variants $cfg [$($variant_meta)* #[$meta]] $($rest)*
+        );
+    };
+    (
+        $block:tt $vis:tt $one_of_block:ident $generics:tt $identifier_meta:tt $identifier_enum:ident $output:tt;
+        [$($variants:tt)*] [] $variant_meta:tt
+        $variant:ident [$($description:literal)?] $($rest:tt)*
+    ) => {
+        $crate::__linked_variants!(
+            $block $vis $one_of_block $generics $identifier_meta $identifier_enum $output;
+            [$($variants)* $variant_meta $variant [$($description)?]] [] [] $($rest)*
+        );
+    };
+    (
+        $block:tt $vis:tt $one_of_block:ident $generics:tt $identifier_meta:tt $identifier_enum:ident $output:tt;
+        [$($variants:tt)*] [$($cfg:meta),+] $variant_meta:tt
+        $variant:ident [$($description:literal)?] $($rest:tt)*
+    ) => {
+        #[cfg(all($($cfg),+))]
+        $crate::__linked_variants!(
+            $block $vis $one_of_block $generics $identifier_meta $identifier_enum $output;
+            [$($variants)* $variant_meta $variant [$($description)?]] [] [] $($rest)*
+        );
+        #[cfg(not(all($($cfg),+)))]
+        $crate::__linked_variants!(
+            $block $vis $one_of_block $generics $identifier_meta $identifier_enum $output;
+            [$($variants)*] [] [] $($rest)*
+        );
+    };
+    (
+        $block:tt $vis:tt $one_of_block:ident $generics:tt $identifier_meta:tt $identifier_enum:ident $output:tt;
+        $variants:tt $cfg:tt $variant_meta:tt $variant:ident : $description:literal $($rest:tt)*
+    ) => {
+        $crate::__linked_variants!(
+            $block $vis $one_of_block $generics $identifier_meta $identifier_enum $output;
+            $variants $cfg $variant_meta $variant [$description] $($rest)*
+        );
+    };
+    (
+        $block:tt $vis:tt $one_of_block:ident $generics:tt $identifier_meta:tt $identifier_enum:ident $output:tt;
+        $variants:tt $cfg:tt $variant_meta:tt $variant:ident $($rest:tt)*
+    ) => {
+        $crate::__linked_variants!(
+            $block $vis $one_of_block $generics $identifier_meta $identifier_enum $output;
+            $variants $cfg $variant_meta $variant [] $($rest)*
+        );
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linked_replace {
+    ( $_replaced:ident $replacement:ty ) => {
+        $replacement
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linked_output {
+    ( [] $vis:vis $identifier_enum:ident; $($variants:ident),* ) => {};
+    ( [$(#[$meta:meta])* $output_enum:ident] $vis:vis $identifier_enum:ident; $($variants:ident),* ) => {
+        /// Output of the first completed future, wrapped in the variant of the future
+        $(#[$meta])*
+        #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        $vis enum $output_enum<$($variants),*> {
+            $( $variants($variants), )*
         }
-    }};
+
+        #[allow(dead_code)]
+        impl<$($variants),*> $output_enum<$($variants),*> {
+            /// Identifier of the completed future
+            pub fn identifier(&self) -> $identifier_enum {
+                match self {
+                    $( $output_enum::$variants(_) => $identifier_enum::$variants, )*
+                }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl<T> $output_enum<$( $crate::__linked_replace!($variants T) ),*> {
+            /// Split the output of futures sharing the same output type into the identifier
+            /// and the value
+            pub fn into_parts(self) -> ($identifier_enum, T) {
+                match self {
+                    $( $output_enum::$variants(value) => ($identifier_enum::$variants, value), )*
+                }
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linked_parts {
+    ( [] $generics:tt $where_clause:tt $vis:vis $one_of_block:ident $identifier_enum:ident; $($variants:ident),* ) => {};
+    (
+        [$parts:ident] [] [] $vis:vis $one_of_block:ident $identifier_enum:ident;
+        $($variants:ident),*
+    ) => {
+        /// Futures returned by [`link_parts`](macro.link_parts.html), which are not linked
+        /// into a block yet. Every field holds the future of the variant it's named after.
+        #[allow(non_snake_case)]
+        #[derive(Debug)]
+        #[must_use = "futures do nothing unless they are linked into a block"]
+        $vis struct $parts<$($variants),*> {
+            $(
+                #[allow(missing_docs)]
+                pub $variants: Option<$variants>,
+            )*
+        }
+
+        impl<$($variants),*> ::core::default::Default for $parts<$($variants),*> {
+            fn default() -> Self {
+                $parts {
+                    $( $variants: None, )*
+                }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl<$($variants),*> $parts<$($variants),*> {
+            /// Number of the futures which are not taken out
+            pub fn len(&self) -> usize {
+                0 $( + self.$variants.is_some() as usize )*
+            }
+
+            /// Returns `true` if all of the futures are taken out
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// Link the futures which are not taken out into a block, as the block returned
+            /// by [`link_futures`](macro.link_futures.html). The futures are linked in the order
+            /// of the variants.
+            pub fn link(
+                self,
+            ) -> $crate::LinkedBlock<
+                $crate::__private::Declared<$one_of_block<$($crate::Identified<$identifier_enum, $variants>),*>>,
+            >
+            where
+                $one_of_block<$($crate::Identified<$identifier_enum, $variants>),*>: ::core::future::Future,
+            {
+                let linked = $crate::FuturesUnordered::new();
+                $(
+                    if let Some(future) = self.$variants {
+                        linked.push($crate::__private::Declared::new(
+                            $identifier_enum::$variants.index(),
+                            $one_of_block::$variants($crate::Identified::new($identifier_enum::$variants, future)),
+                        ));
+                    }
+                )*
+                $crate::LinkedBlock::new(linked)
+            }
+        }
+    };
+    ( [$parts:ident] $generics:tt $where_clause:tt $vis:vis $one_of_block:ident $identifier_enum:ident; $($variants:ident),* ) => {
+        compile_error!("parts can't be generated for blocks with generic parameters or a `where` section");
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linked_one_of {
+    (
+        @impl [$(#[$meta:meta])*] [$vis:vis] $one_of_block:ident
+        [$($generic:ident),*] [$($where_clause:tt)*] [$($phantom:tt)*] [$($phantom_arm:tt)*];
+        $head:ident $(, $tail:ident)*
+    ) => {
+        /// Combines multiple different futures or streams having the
+        /// same associated types into a single type.
+        $(#[$meta])*
+        #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        $vis enum $one_of_block<$($generic,)* $head $(, $tail)*>
+        where
+            $($where_clause)*
+        {
+            $head($head),
+            $($tail($tail),)*
+            $($phantom)*
+        }
+
+        impl<$($generic,)* $head $(, $tail)*> ::core::future::Future
+            for $one_of_block<$($generic,)* $head $(, $tail)*>
+        where
+            $head: ::core::future::Future,
+            $( $tail: ::core::future::Future<Output = $head::Output>, )*
+            $($where_clause)*
+        {
+            type Output = $head::Output;
+
+            fn poll(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<Self::Output> {
+                unsafe {
+                    match self.get_unchecked_mut() {
+                        $one_of_block::$head(x) => ::core::pin::Pin::new_unchecked(x).poll(cx),
+                        $( $one_of_block::$tail(x) => ::core::pin::Pin::new_unchecked(x).poll(cx), )*
+                        $($phantom_arm)*
+                    }
+                }
+            }
+        }
+
+        impl<$($generic,)* $head $(, $tail)*> $crate::FusedFuture
+            for $one_of_block<$($generic,)* $head $(, $tail)*>
+        where
+            $head: $crate::FusedFuture,
+            $( $tail: $crate::FusedFuture<Output = $head::Output>, )*
+            $($where_clause)*
+        {
+            fn is_terminated(&self) -> bool {
+                match self {
+                    $one_of_block::$head(x) => x.is_terminated(),
+                    $( $one_of_block::$tail(x) => x.is_terminated(), )*
+                    $($phantom_arm)*
+                }
+            }
+        }
+
+        impl<$($generic,)* $head $(, $tail)*> $crate::Stream
+            for $one_of_block<$($generic,)* $head $(, $tail)*>
+        where
+            $head: $crate::Stream,
+            $( $tail: $crate::Stream<Item = $head::Item>, )*
+            $($where_clause)*
+        {
+            type Item = $head::Item;
+
+            fn poll_next(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<Option<Self::Item>> {
+                unsafe {
+                    match self.get_unchecked_mut() {
+                        $one_of_block::$head(x) => ::core::pin::Pin::new_unchecked(x).poll_next(cx),
+                        $( $one_of_block::$tail(x) => ::core::pin::Pin::new_unchecked(x).poll_next(cx), )*
+                        $($phantom_arm)*
+                    }
+                }
+            }
+        }
+
+        impl<$($generic,)* $head $(, $tail)*> $crate::FusedStream
+            for $one_of_block<$($generic,)* $head $(, $tail)*>
+        where
+            $head: $crate::FusedStream,
+            $( $tail: $crate::FusedStream<Item = $head::Item>, )*
+            $($where_clause)*
+        {
+            fn is_terminated(&self) -> bool {
+                match self {
+                    $one_of_block::$head(x) => x.is_terminated(),
+                    $( $one_of_block::$tail(x) => x.is_terminated(), )*
+                    $($phantom_arm)*
+                }
+            }
+        }
+    };
+    ( $(#[$meta:meta])* $vis:vis $one_of_block:ident; $($variants:ident),* ) => {
+        $crate::__linked_one_of!($(#[$meta])* $vis $one_of_block [] []; $($variants),*);
+    };
+    (
+        $(#[$meta:meta])* $vis:vis $one_of_block:ident [] [$($where_clause:tt)*];
+        $($variants:ident),*
+    ) => {
+        $crate::__linked_one_of!(
+            @impl [$(#[$meta])*] [$vis] $one_of_block [] [$($where_clause)*] [] [];
+            $($variants),*
+        );
+    };
+    (
+        $(#[$meta:meta])* $vis:vis $one_of_block:ident [$($generic:ident),+] [$($where_clause:tt)*];
+        $($variants:ident),*
+    ) => {
+        $crate::__linked_one_of!(
+            @impl [$(#[$meta])*] [$vis] $one_of_block [$($generic),+] [$($where_clause)*]
+            [
+                #[doc(hidden)]
+                __LinkedFuturesPhantom(
+                    ::core::marker::PhantomData<fn() -> ($($generic,)+)>,
+                    ::core::convert::Infallible,
+                ),
+            ]
+            [ $one_of_block::__LinkedFuturesPhantom(_, never) => match *never {}, ];
+            $($variants),*
+        );
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linked_identifier {
+    (
+        $(#[$meta:meta])* $vis:vis $identifier_enum:ident;
+        $( $(#[$variant_meta:meta])* $variants:ident $(: $description:literal)? ),*
+    ) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        $vis enum $identifier_enum {
+            $( $(#[$variant_meta])* $variants ),*
+        }
+
+        #[allow(dead_code)]
+        impl $identifier_enum {
+            /// Number of linked futures
+            pub const COUNT: usize = [$( stringify!($variants) ),*].len();
+
+            /// All of the identifiers, in the order of declaration
+            pub const ALL: [Self; Self::COUNT] = [$( $identifier_enum::$variants ),*];
+
+            /// Iterate over all of the identifiers, in the order of declaration
+            pub fn iter() -> ::core::array::IntoIter<Self, { Self::COUNT }> {
+                ::core::iter::IntoIterator::into_iter(Self::ALL)
+            }
+
+            /// Name of the variant
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    $( $identifier_enum::$variants => stringify!($variants), )*
+                }
+            }
+
+            /// Description of the linked future, or an empty string if it wasn't described
+            pub const fn description(self) -> &'static str {
+                match self {
+                    $( $identifier_enum::$variants => concat!($($description)?), )*
+                }
+            }
+
+            /// Position of the identifier in the order of declaration
+            pub const fn index(self) -> usize {
+                self as usize
+            }
+
+            /// Identifier at the position in the order of declaration
+            pub const fn from_index(index: usize) -> Option<Self> {
+                if index < Self::COUNT {
+                    Some(Self::ALL[index])
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for $identifier_enum {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl ::core::str::FromStr for $identifier_enum {
+            type Err = $crate::ParseIdentifierError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $( stringify!($variants) => Ok($identifier_enum::$variants), )*
+                    _ => Err($crate::ParseIdentifierError::new(s)),
+                }
+            }
+        }
+
+        impl ::core::convert::From<$identifier_enum> for usize {
+            fn from(identifier: $identifier_enum) -> usize {
+                identifier.index()
+            }
+        }
+
+        impl $crate::__private::Completion for $identifier_enum {
+            type Identifier = Self;
+            type Output = ();
+
+            fn into_completion(self) -> (Self, ()) {
+                (self, ())
+            }
+        }
+
+        $crate::__linked_identifier_serde!($identifier_enum; $($variants),*);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linked_identifier_serde {
+    ( $identifier_enum:ident; $($variants:ident),* ) => {
+        impl $crate::__private::serde::Serialize for $identifier_enum {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: $crate::__private::serde::Serializer,
+            {
+                $crate::__private::serialize_identifier(
+                    serializer,
+                    stringify!($identifier_enum),
+                    self.index(),
+                    &[$( stringify!($variants) ),*],
+                )
+            }
+        }
+
+        impl<'de> $crate::__private::serde::Deserialize<'de> for $identifier_enum {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: $crate::__private::serde::Deserializer<'de>,
+            {
+                $crate::__private::deserialize_identifier(
+                    deserializer,
+                    stringify!($identifier_enum),
+                    &[$( stringify!($variants) ),*],
+                    Self::from_index,
+                )
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linked_identifier_serde {
+    ( $identifier_enum:ident; $($variants:ident),* ) => {};
+}
+
+/// Link multiple futures into a single block
+///
+/// The block is a [`LinkedBlock`](struct.LinkedBlock.html), which resolves with the identifier
+/// and the output of the first completed future.
+///
+/// Example:
+/// ```rust
+/// use std::time::Duration;
+///
+/// use futures::{pin_mut, SinkExt, StreamExt};
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use tokio::time::{delay_for, interval, Instant};
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(PeriodicStoppableSender, PeriodicStoppableSenderFutureIdentifier;
+///     Forwarder,
+///     Reader,
+///     Generator,
+///     Stop
+/// );
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut tx1, mut rx1) = mpsc::channel::<Instant>(1);
+///     let (mut tx2, mut rx2) = mpsc::channel::<Instant>(1);
+///
+///     let mut interval = interval(Duration::from_millis(100));
+///
+///     let generator = async {
+///         while let Some(instant) = interval.next().await {
+///             tx1.send(instant).await;
+///         }
+///     };
+///     let forwarder = async {
+///         while let Some(instant) = rx1.next().await {
+///             tx2.send(instant).await;
+///         }
+///     };
+///     let reader = async {
+///         while let Some(instant) = rx2.next().await {
+///             println!("instant: {:?}", instant);
+///         }
+///     };
+///     let stop = async {
+///         delay_for(Duration::from_secs(1)).await;
+///     };
+///     let linked = link_futures!(
+///        PeriodicStoppableSender,
+///        PeriodicStoppableSenderFutureIdentifier;
+///        Generator => generator,
+///        Forwarder => forwarder,
+///        Reader => reader,
+///        Stop => stop
+///     );
+///     block_on(async {
+///         pin_mut!(linked);
+///         let (completed_future_identifier, _) = linked.await;
+///         match completed_future_identifier {
+///             PeriodicStoppableSenderFutureIdentifier::Stop =>
+///                 println!("linked block stopped normally"),
+///             n =>
+///                 panic!("linked block unexpectedly terminated by future: {:?}", n),
+///         }
+///     });
+/// }
+/// ```
+///
+/// Every variant declared in the block has to be linked. Variants which are missing are
+/// reported as non-exhaustive patterns of the identifier enum:
+/// ```rust,compile_fail,E0004
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Writer, Stop);
+///
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     Reader => async {},
+///     Stop => async {}
+/// );
+/// ```
+///
+/// Linking the same future twice is rejected with an error naming the duplicate, and so is
+/// declaring the same variant twice in [`linked_block`](macro.linked_block.html). Futures
+/// which aren't declared in the block are reported as missing variants:
+/// ```rust,compile_fail
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Stop);
+///
+/// // error: future `Stop` is linked more than once
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     Reader => async {},
+///     Stop => async {},
+///     Stop => async {}
+/// );
+/// ```
+///
+/// The block and identifier names may be paths, so blocks can be declared in one module
+/// and linked in another:
+/// ```rust
+/// mod blocks {
+///     linked_futures::linked_block!(pub Block, BlockFutureIdentifier; Reader, Stop);
+/// }
+///
+/// let block = linked_futures::link_futures!(blocks::Block, blocks::BlockFutureIdentifier;
+///     Reader => futures::future::pending::<()>(),
+///     Stop => async {}
+/// );
+/// let (completed_future_identifier, _) = futures::executor::block_on(block);
+/// assert_eq!(completed_future_identifier, blocks::BlockFutureIdentifier::Stop);
+/// ```
+///
+/// Variants which are intentionally unused should be marked with `skip`. Skipped variants
+/// are linked as futures which never complete:
+/// ```rust
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Writer, Stop);
+///
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     Reader => async {},
+///     Writer => skip,
+///     Stop => async {}
+/// );
+/// ```
+///
+/// Variants linked with the `optional` keyword take an `Option` of a future, and are linked as
+/// futures which never complete if it's `None`:
+/// ```rust
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Service, ServiceFutureIdentifier; Serve, MetricsFlusher);
+///
+/// let metrics_enabled = false;
+/// let block = link_futures!(Service, ServiceFutureIdentifier;
+///     Serve => async { "served" },
+///     MetricsFlusher => optional metrics_enabled.then(|| async { "flushed" })
+/// );
+/// let (completed_future_identifier, output) = block_on(block);
+/// assert_eq!(completed_future_identifier, ServiceFutureIdentifier::Serve);
+/// assert_eq!(output, "served");
+/// ```
+///
+/// The future expressions are evaluated once the block is polled for the first time, rather
+/// than when the block is created, so a future with side effects in its construction, such as
+/// opening a socket, isn't constructed until the block runs. Futures may also be linked as
+/// closures returning them, which are called once the block is polled for the first time:
+/// ```rust
+/// use std::cell::Cell;
+///
+/// use futures::executor::block_on;
+/// use futures::future::{pending, ready};
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Client, ClientFutureIdentifier; Reader, Stop);
+///
+/// let connections = Cell::new(0);
+/// let connect = || {
+///     connections.set(connections.get() + 1);
+///     pending::<()>()
+/// };
+/// let block = link_futures!(Client, ClientFutureIdentifier;
+///     Reader => || connect(),
+///     Stop => move || ready(())
+/// );
+/// assert_eq!(connections.get(), 0);
+/// block_on(block);
+/// assert_eq!(connections.get(), 1);
+/// ```
+///
+/// A variant followed by an `if` guard is only linked if the guard is `true` once the block is
+/// created, while the identifier enum stays the same. If every guard is `false`, the block
+/// returned by `link_futures` never resolves, while the blocks resolving once all of the
+/// futures complete, such as the block of `join_futures`, resolve right away:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{join_futures, linked_block};
+///
+/// linked_block!(Service, ServiceFutureIdentifier; Serve, Tracing);
+///
+/// struct Config {
+///     tracing_enabled: bool,
+/// }
+///
+/// let config = Config { tracing_enabled: false };
+/// let block = join_futures!(Service, ServiceFutureIdentifier;
+///     Serve => async { "served" },
+///     Tracing if config.tracing_enabled => pending()
+/// );
+/// let outputs = block_on(block);
+/// assert_eq!(outputs.len(), 1);
+/// assert_eq!(outputs[&ServiceFutureIdentifier::Serve], "served");
+/// ```
+///
+/// Variants linked with the `blocking` keyword take a closure doing blocking work, which is run
+/// with [`Spawner::spawn_blocking`](trait.Spawner.html#method.spawn_blocking) once the block
+/// is polled for the first time, and resolve with the closure's output. A panic in the closure
+/// is resumed when the variant is polled. The closure is run by the
+/// [`DefaultSpawner`](type.DefaultSpawner.html) with `tokio::task::spawn_blocking`, which
+/// requires the `tokio` feature, or by the spawner given with the `spawner = S` option:
+/// ```rust
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Storage, StorageFutureIdentifier; Server, Compaction);
+///
+/// fn compact(segments: Vec<u32>) -> usize {
+///     std::thread::sleep(std::time::Duration::from_millis(10));
+///     segments.len()
+/// }
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main]
+/// async fn main() {
+///     let segments = vec![1, 2, 3];
+///     let block = link_futures!(Storage, StorageFutureIdentifier;
+///         Server => futures::future::pending(),
+///         Compaction => blocking move || compact(segments)
+///     );
+///     let (completed_future_identifier, compacted) = block.await;
+///     assert_eq!(completed_future_identifier, StorageFutureIdentifier::Compaction);
+///     assert_eq!(compacted, 3);
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+///
+/// Variants linked with the `thread` keyword take a closure, which is run on a dedicated OS
+/// thread as a [`LinkedThread`](struct.LinkedThread.html). The closure is given a
+/// [`ThreadStop`](struct.ThreadStop.html) flag, which is set once the block completes, and
+/// the thread is joined in the background, without blocking the executor.
+///
+/// With the `signals` feature enabled, variants linked with the `termination_signal` keyword
+/// complete once the process receives `SIGINT` or `SIGTERM` (ctrl-c on Windows), resolving
+/// with the received [`Signal`](enum.Signal.html), see
+/// [`termination_signal`](fn.termination_signal.html).
+///
+/// Futures returning `Result` with different error types may be linked with a common error
+/// type following the identifier name. The errors are converted with `Into`, so the block
+/// resolves with `Result<T, Error>`. Catch-all error types, such as `anyhow::Error` or
+/// `eyre::Report`, may be used as the common type the same way, since they are converted
+/// from any error type; the identifier returned with the output tells which future failed:
+/// ```rust
+/// use std::fmt;
+/// use std::io;
+/// use std::num::ParseIntError;
+///
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// #[derive(Debug)]
+/// enum ServiceError {
+///     Io(io::Error),
+///     Parse(ParseIntError),
+/// }
+///
+/// impl From<io::Error> for ServiceError {
+///     fn from(err: io::Error) -> Self {
+///         ServiceError::Io(err)
+///     }
+/// }
+///
+/// impl From<ParseIntError> for ServiceError {
+///     fn from(err: ParseIntError) -> Self {
+///         ServiceError::Parse(err)
+///     }
+/// }
+///
+/// linked_block!(Service, ServiceFutureIdentifier; Reader, Parser);
+///
+/// let block = link_futures!(Service, ServiceFutureIdentifier, error = ServiceError;
+///     Reader => futures::future::pending::<Result<(), io::Error>>(),
+///     Parser => async { "ten".parse::<u8>().map(|_| ()) }
+/// );
+/// let (completed_future_identifier, result) = block_on(block);
+/// assert_eq!(completed_future_identifier, ServiceFutureIdentifier::Parser);
+/// assert!(matches!(result, Err(ServiceError::Parse(_))));
+/// ```
+///
+/// With `identified_error = Error` instead, the errors are wrapped in
+/// [`IdentifiedError`](struct.IdentifiedError.html) with the identifier of the future before
+/// they are converted, so catch-all error types hold the identifier as the context of the
+/// error:
+/// ```rust
+/// use std::error::Error;
+/// use std::num::ParseIntError;
+///
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Service, ServiceFutureIdentifier; Reader, Parser);
+///
+/// let block = link_futures!(Service, ServiceFutureIdentifier,
+///     identified_error = Box<dyn Error + Send + Sync>;
+///     Reader => futures::future::pending::<Result<(), std::io::Error>>(),
+///     Parser => async { "ten".parse::<u8>().map(|_| ()) }
+/// );
+/// let (_, result) = block_on(block);
+/// let err = result.unwrap_err();
+/// assert_eq!(err.to_string(), "linked future `Parser` failed");
+/// assert!(err.source().unwrap().is::<ParseIntError>());
+/// ```
+///
+/// Conditionally compiled variants are linked under the same `#[cfg]` attribute:
+/// ```rust
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, #[cfg(feature = "metrics")] Metrics, Stop);
+///
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     Reader => async {},
+///     #[cfg(feature = "metrics")]
+///     Metrics => async {},
+///     Stop => async {}
+/// );
+/// ```
+///
+/// Futures marked with `#[restart]` are linked as a factory closure instead of a future.
+/// When the future returned by the factory completes, its output is dropped and the factory
+/// is called again, so the block is only terminated by the other futures:
+/// ```rust
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use futures::StreamExt;
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Worker, Stop);
+///
+/// let (tx, rx) = mpsc::unbounded();
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     #[restart]
+///     Worker => || {
+///         let tx = tx.clone();
+///         async move { tx.unbounded_send(()).unwrap() }
+///     },
+///     Stop => rx.take(3).count()
+/// );
+/// let (completed_future_identifier, runs) = block_on(block);
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Stop);
+/// assert_eq!(runs, 3);
+/// ```
+///
+/// Factories of futures returning `Result` may be marked with `#[restart(severity)]`
+/// instead, so the future is only restarted after recoverable errors, while a fatal error
+/// completes it. The errors are classified by the [`Severity`](trait.Severity.html) trait,
+/// or by a closure marking them with `#[restart(fatal = |err| ...)]`.
+///
+/// Restarts may be limited with `#[restart(max = restarts)]`, or with
+/// `#[restart(max = restarts, within = window)]` to only count the restarts within the
+/// `window` duration. Once the future is restarted once more, it completes with
+/// `Err(Escalated(identifier))`, so it's linked with futures returning `Result`, with the
+/// error converted with `error = Error` if needed:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, Escalated};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Worker, Stop);
+///
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     #[restart(max = 3)]
+///     Worker => || async {},
+///     Stop => pending::<Result<(), _>>()
+/// );
+/// let (completed_future_identifier, result) = block_on(block);
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Worker);
+/// assert_eq!(result, Err(Escalated(BlockFutureIdentifier::Worker)));
+/// ```
+///
+/// With the `tokio` feature enabled, futures marked with `#[within(duration)]` complete with
+/// `Err(TimedOut)` if they don't complete in time, and with `Ok` wrapping their output
+/// otherwise. Futures returning `Result` may be marked with `#[within(duration, error)]`
+/// instead, so the timeout is converted into their own error type:
+/// ```rust
+/// use std::io;
+/// use std::time::Duration;
+///
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, TimedOut};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Connect, Handshake);
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main]
+/// async fn main() {
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         #[within(Duration::from_millis(10))]
+///         Connect => pending::<()>(),
+///         Handshake => pending()
+///     );
+///     assert_eq!(block.await, (BlockFutureIdentifier::Connect, Err(TimedOut)));
+///
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         #[within(Duration::from_millis(10), error)]
+///         Connect => pending::<io::Result<()>>(),
+///         Handshake => pending()
+///     );
+///     let (completed_future_identifier, result) = block.await;
+///     assert_eq!(completed_future_identifier, BlockFutureIdentifier::Connect);
+///     assert_eq!(result.unwrap_err().kind(), io::ErrorKind::TimedOut);
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+///
+/// Work running on a schedule is linked with `#[schedule(instants)]`, where `instants` is
+/// anything iterable over `Instant`s, like a vector, or the upcoming times of a cron
+/// expression parsed by another crate. Like with `#[every]`, the closure is called with
+/// the scheduled instant, and the future never terminates the block, even after the last
+/// instant:
+/// ```rust
+/// use std::time::{Duration, Instant};
+///
+/// use futures::channel::mpsc;
+/// use futures::StreamExt;
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Report, Stop);
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main]
+/// async fn main() {
+///     let now = Instant::now();
+///     let instants = vec![now + Duration::from_millis(1), now + Duration::from_millis(5)];
+///     let (tx, rx) = mpsc::unbounded();
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         #[schedule(instants.clone())]
+///         Report => |instant| {
+///             let tx = tx.clone();
+///             async move { tx.unbounded_send(instant).unwrap() }
+///         },
+///         Stop => rx.take(2).collect::<Vec<_>>()
+///     );
+///     let (_, reported) = block.await;
+///     assert_eq!(reported, instants);
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+///
+/// The first poll of futures marked with `#[delay(duration)]` is delayed, so that they
+/// don't all start at the same instant. With a range of durations, the delay is picked
+/// randomly from the range. Delays require the `tokio` feature:
+/// ```rust
+/// use std::time::{Duration, Instant};
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Poller, Stop);
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main]
+/// async fn main() {
+///     let started_at = Instant::now();
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         #[delay(Duration::from_millis(10)..Duration::from_millis(20))]
+///         Poller => async { Instant::now() },
+///         Stop => futures::future::pending()
+///     );
+///     let (_, polled_at) = block.await;
+///     assert!(polled_at - started_at >= Duration::from_millis(10));
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+///
+/// Futures which may hang while starting up are marked with `#[startup(duration)]`, and
+/// linked as a closure receiving a [`Startup`](struct.Startup.html) handle. Unless the
+/// future completes, or marks its startup as complete within the duration, it completes
+/// with `Err(TimedOut)`. `#[startup(duration, error)]` converts the timeout into the error
+/// of the future, like `#[within]`:
+/// ```rust
+/// use std::time::Duration;
+///
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, TimedOut};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Server, Stop);
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main]
+/// async fn main() {
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         #[startup(Duration::from_millis(10))]
+///         Server => |startup| async move {
+///             startup.complete();
+///             tokio::time::delay_for(Duration::from_millis(20)).await;
+///         },
+///         Stop => pending()
+///     );
+///     assert_eq!(block.await, (BlockFutureIdentifier::Server, Ok(())));
+///
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         #[startup(Duration::from_millis(10))]
+///         Server => |_startup| pending::<()>(),
+///         Stop => pending()
+///     );
+///     assert_eq!(block.await, (BlockFutureIdentifier::Server, Err(TimedOut)));
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+///
+/// Futures returning `Result` may be marked with `#[retry(max = retries)]`, or with
+/// `#[retry(max = retries, backoff = backoff)]` to wait between the attempts according to
+/// the [`Backoff`](struct.Backoff.html). They are linked as a factory closure, which is
+/// called again when the future fails, up to `retries` times. Once all of the attempts
+/// fail, the future completes with [`Exhausted`](struct.Exhausted.html), holding the last
+/// error and the number of attempts. Retries require the `tokio` feature:
+/// ```rust
+/// use std::time::Duration;
+///
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, Backoff};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Connect, Stop);
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main]
+/// async fn main() {
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         #[retry(max = 3, backoff = Backoff::exponential(Duration::from_millis(1)))]
+///         Connect => || async { "localhost:0".parse::<std::net::SocketAddr>().map(|_| ()) },
+///         Stop => pending()
+///     );
+///     let (completed_future_identifier, result) = block.await;
+///     assert_eq!(completed_future_identifier, BlockFutureIdentifier::Connect);
+///     assert_eq!(result.unwrap_err().attempts, 4);
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+///
+/// Periodic work is linked with `#[every(period)]` and a closure, which is called with
+/// the number of the tick once per period and returns the future doing the work. Ticks
+/// missed while the work is running are fired immediately after it. Periodic futures
+/// never terminate the block, and also require the `tokio` feature:
+/// ```rust
+/// use std::time::Duration;
+///
+/// use futures::channel::mpsc;
+/// use futures::StreamExt;
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Heartbeat, Stop);
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main]
+/// async fn main() {
+///     let (tx, rx) = mpsc::unbounded();
+///     let block = link_futures!(Block, BlockFutureIdentifier;
+///         #[every(Duration::from_millis(1))]
+///         Heartbeat => |tick| {
+///             let tx = tx.clone();
+///             async move { tx.unbounded_send(tick).unwrap() }
+///         },
+///         Stop => rx.take(3).collect::<Vec<_>>()
+///     );
+///     let (completed_future_identifier, ticks) = block.await;
+///     assert_eq!(completed_future_identifier, BlockFutureIdentifier::Stop);
+///     assert_eq!(ticks, [0, 1, 2]);
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+///
+/// The time-based attributes use the [`DefaultTimer`](type.DefaultTimer.html), which requires
+/// the `tokio` feature. Another [`Timer`](trait.Timer.html) may follow the identifier name
+/// as `timer = T`, after the error type if there is one, and is used by all of the
+/// time-based attributes of the block instead:
+/// ```rust
+/// use std::time::Duration;
+///
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_futures, linked_block, ThreadTimer};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Ticker, Stop);
+///
+/// let block = link_futures!(Block, BlockFutureIdentifier, timer = ThreadTimer;
+///     #[every(Duration::from_millis(1))]
+///     Ticker => |_| async {},
+///     #[delay(Duration::from_millis(5))]
+///     Stop => async {}
+/// );
+/// let (completed_future_identifier, _) = block_on(block);
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Stop);
+/// ```
+///
+/// In the same way, the `blocking` variants and the tasks of
+/// [`link_spawned_futures`](macro.link_spawned_futures.html) and
+/// [`link_local_futures`](macro.link_local_futures.html) use the
+/// [`DefaultSpawner`](type.DefaultSpawner.html), unless another
+/// [`Spawner`](trait.Spawner.html) follows the timer as `spawner = S`:
+/// ```rust
+/// use core::future::Future;
+///
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, Spawner};
+///
+/// /// Spawner running every task on its own thread
+/// struct ThreadSpawner;
+///
+/// impl Spawner for ThreadSpawner {
+///     fn spawn<F>(task: F)
+///     where
+///         F: Future<Output = ()> + Send + 'static,
+///     {
+///         std::thread::spawn(move || block_on(task));
+///     }
+/// }
+///
+/// linked_block!(Block, BlockFutureIdentifier; Server, Checksum);
+///
+/// let block = link_futures!(Block, BlockFutureIdentifier, spawner = ThreadSpawner;
+///     Server => pending(),
+///     Checksum => blocking || (1..=4u32).sum::<u32>()
+/// );
+/// assert_eq!(block_on(block), (BlockFutureIdentifier::Checksum, 10));
+/// ```
+///
+/// Given a spawner, the cleanup hooks of the futures marked with `#[on_cancel]` run as tasks
+/// spawned with it, and the futures marked with `#[watch]` spawn the monitor of a
+/// [`Watchdog::monitored`](struct.Watchdog.html#method.monitored) watchdog with it.
+///
+/// Futures marked with `#[detached]` run until the block is terminated, but their own
+/// completion doesn't terminate it:
+/// ```rust
+/// use futures::channel::oneshot;
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Flusher, Stop);
+///
+/// let (tx, rx) = oneshot::channel();
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     #[detached]
+///     Flusher => async { tx.send(()).unwrap() },
+///     Stop => async { rx.await.unwrap() }
+/// );
+/// let (completed_future_identifier, _) = block_on(block);
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Stop);
+/// ```
+///
+/// Panics of the futures marked with `#[catch_unwind]` carry the identifier of the future,
+/// so the block resolves with [`Panicked`](struct.Panicked.html) instead of unwinding when it's
+/// awaited with [`LinkedBlock::catch_unwind`](struct.LinkedBlock.html#method.catch_unwind).
+/// Futures marked with `#[on_panic(policy)]` are linked as a factory closure, and their
+/// panics are handled according to the [`PanicPolicy`](enum.PanicPolicy.html).
+#[macro_export]
+macro_rules! link_futures {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+ $(, error = $error:ty)?
+        $(, identified_error = $identified_error:ty)? $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($one_of_block)::+] [$($identifier_enum)::+] [tuple]
+                [$($error)? $(identified $identified_error)?] [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Define a block, which may be built multiple times from the factories of its futures
+///
+/// Works like [`link_futures`](macro.link_futures.html), but every variant is linked as a
+/// factory, a closure returning the future, and the macro returns a
+/// [`LinkedFactory`](struct.LinkedFactory.html). Every call of
+/// [`rebuild`](struct.LinkedFactory.html#method.rebuild) calls all of the factories, and links
+/// the new futures into a new block, so the same definition may run once per reconnect
+/// attempt or per test iteration. The factories must be `Fn` closures, which usually clone
+/// their captured state into the futures.
+///
+/// Example:
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_factories, linked_block};
+///
+/// linked_block!(Client, ClientFutureIdentifier; Reader, Disconnect);
+///
+/// let attempts = Arc::new(AtomicUsize::new(0));
+/// let client = link_factories!(Client, ClientFutureIdentifier;
+///     Reader => || pending::<usize>(),
+///     Disconnect => || {
+///         let attempts = attempts.clone();
+///         async move { attempts.fetch_add(1, Ordering::SeqCst) + 1 }
+///     }
+/// );
+/// for attempt in 1..=3 {
+///     let (completed_future_identifier, output) = block_on(client.rebuild());
+///     assert_eq!(completed_future_identifier, ClientFutureIdentifier::Disconnect);
+///     assert_eq!(output, attempt);
+/// }
+/// ```
+#[macro_export]
+macro_rules! link_factories {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+ $(, error = $error:ty)?
+        $(, identified_error = $identified_error:ty)? $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $( $key:ident => $factory:expr ),* $(,)?
+    ) => {{
+        #[allow(non_snake_case)]
+        let ($($key,)*) = ($($factory,)*);
+        $crate::LinkedFactory::new(move || {
+            #[allow(non_snake_case)]
+            let ($($key,)*) = ($($key(),)*);
+            $crate::link_futures!(
+                $($one_of_block)::+, $($identifier_enum)::+ $(, error = $error)?
+                $(, identified_error = $identified_error)? $(, timer = $timer)?
+                $(, spawner = $spawner)?;
+                $( $key => $key ),*
+            )
+        })
+    }};
+}
+
+/// Prepare multiple futures to be linked into a block, without linking them yet
+///
+/// Takes the same arguments as [`link_futures`](macro.link_futures.html), with the name of
+/// the parts generated by [`linked_block`](macro.linked_block.html) in place of the block name,
+/// and returns the parts, which may be passed around, inspected and modified before they are
+/// linked into the block returned by `link_futures`. The parts have a field holding the future
+/// of each variant, which is `None` if the future is disabled by a guard. The futures can no
+/// longer be taken apart once they are linked, as the block pins them.
+///
+/// Example:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::{pending, Pending, Ready};
+///
+/// use linked_futures::{link_parts, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier, parts = BlockParts; Reader, Stop);
+///
+/// fn assemble() -> BlockParts<Pending<()>, Ready<()>> {
+///     link_parts!(BlockParts, BlockFutureIdentifier;
+///         Reader => pending(),
+///         Stop => futures::future::ready(())
+///     )
+/// }
+///
+/// let mut parts = assemble();
+/// assert_eq!(parts.len(), 2);
+/// parts.Reader = None;
+/// assert_eq!(parts.len(), 1);
+/// let (completed_future_identifier, ()) = block_on(parts.link());
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Stop);
+/// ```
+///
+/// Taking a future out of its field returns the future passed to the macro, with its own type,
+/// and it resolves with its own output, without the identifier. Futures with attributes are
+/// stored with the attribute applied, and an `error = T` conversion is applied to every future,
+/// so their fields hold `async` blocks instead.
+#[macro_export]
+macro_rules! link_parts {
+    (
+        $($parts:ident)::+, $($identifier_enum:ident)::+ $(, error = $error:ty)?
+        $(, identified_error = $identified_error:ty)? $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($parts)::+] [$($identifier_enum)::+] [parts]
+                [$($error)? $(identified $identified_error)?] [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Link multiple futures into a block, spawning each of them as a separate tokio task
+///
+/// Works like [`link_futures`](macro.link_futures.html), but returns a
+/// [`LinkedTaskBlock`](struct.LinkedTaskBlock.html), so the futures run in parallel on the
+/// worker threads of the runtime, instead of being polled by the task awaiting the block.
+/// Once a future completes, the rest of the tasks are aborted. The futures must be `Send`
+/// and `'static`. The tasks are spawned with the
+/// [`DefaultSpawner`](type.DefaultSpawner.html), so the macro must be called from the context
+/// of a tokio runtime and requires the `tokio` feature, unless another
+/// [`Spawner`](trait.Spawner.html) is given with the `spawner = S` option.
+///
+/// Example:
+/// ```rust
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_spawned_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Hasher, Stop);
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main]
+/// async fn main() {
+///     let block = link_spawned_futures!(Block, BlockFutureIdentifier;
+///         Hasher => pending::<&str>(),
+///         Stop => async { "stopped" }
+///     );
+///     let (completed_future_identifier, output) = block.await;
+///     assert_eq!(completed_future_identifier, BlockFutureIdentifier::Stop);
+///     assert_eq!(output, "stopped");
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! link_spawned_futures {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+ $(, error = $error:ty)?
+        $(, identified_error = $identified_error:ty)? $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($one_of_block)::+] [$($identifier_enum)::+] [spawned]
+                [$($error)? $(identified $identified_error)?] [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Link multiple futures into a block, spawning each of them into a tokio `JoinSet`
+///
+/// Works like [`link_spawned_futures`](macro.link_spawned_futures.html), but returns a
+/// [`LinkedJoinSet`](struct.LinkedJoinSet.html), which owns the tasks with the `JoinSet` of
+/// tokio 1 and resolves with the identifier of the first completed future, paired with the
+/// result of its task. A panic of a future is reported as the `JoinError` of the task. The
+/// futures must be `Send` and `'static`, and the macro must be called from the context of a
+/// tokio 1 runtime. Requires the `join-set` feature.
+///
+/// Example:
+/// ```rust
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_join_set_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Hasher, Stop);
+///
+/// let runtime = tokio1::runtime::Builder::new_current_thread().build().unwrap();
+/// runtime.block_on(async {
+///     let block = link_join_set_futures!(Block, BlockFutureIdentifier;
+///         Hasher => pending::<&str>(),
+///         Stop => async { "stopped" }
+///     );
+///     let (completed_future_identifier, result) = block.await;
+///     assert_eq!(completed_future_identifier, BlockFutureIdentifier::Stop);
+///     assert_eq!(result.unwrap(), "stopped");
+/// });
+/// ```
+#[cfg(feature = "join-set")]
+#[macro_export]
+macro_rules! link_join_set_futures {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+ $(, error = $error:ty)?
+        $(, identified_error = $identified_error:ty)? $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($one_of_block)::+] [$($identifier_enum)::+] [join_set]
+                [$($error)? $(identified $identified_error)?] [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Link multiple futures into a block, spawning each of them as a local tokio task
+///
+/// Works like [`link_spawned_futures`](macro.link_spawned_futures.html), but the futures are
+/// spawned as local tasks, so they don't have to be `Send`. The blocks of the other linking
+/// macros never require the futures to be `Send` either, as long as they are not spawned. The
+/// tasks are spawned into the current tokio `LocalSet` with the
+/// [`DefaultSpawner`](type.DefaultSpawner.html), so the macro requires the `tokio` feature,
+/// unless another [`LocalSpawner`](trait.LocalSpawner.html) is given with the `spawner = S`
+/// option.
+///
+/// Example:
+/// ```rust
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// use linked_futures::{link_local_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Writer, Reader);
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main(basic_scheduler)]
+/// async fn main() {
+///     let state = Rc::new(RefCell::new(Vec::new()));
+///     let writer_state = state.clone();
+///     let local = tokio::task::LocalSet::new();
+///     let (completed_future_identifier, len) = local
+///         .run_until(async move {
+///             link_local_futures!(Block, BlockFutureIdentifier;
+///                 Writer => async move {
+///                     writer_state.borrow_mut().push(1);
+///                     futures::future::pending::<usize>().await
+///                 },
+///                 Reader => async move {
+///                     tokio::task::yield_now().await;
+///                     state.borrow().len()
+///                 }
+///             )
+///             .await
+///         })
+///         .await;
+///     assert_eq!(completed_future_identifier, BlockFutureIdentifier::Reader);
+///     assert_eq!(len, 1);
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! link_local_futures {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+ $(, error = $error:ty)?
+        $(, identified_error = $identified_error:ty)? $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($one_of_block)::+] [$($identifier_enum)::+] [local]
+                [$($error)? $(identified $identified_error)?] [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Link multiple futures into a block, which polls them in the order of declaration
+///
+/// Works like [`link_futures`](macro.link_futures.html), but the futures aren't polled
+/// through `FuturesUnordered`: every time the block is woken, all of the futures are polled
+/// in order, and the first one which is ready terminates the block, like in `select_biased!`.
+/// When multiple futures are ready at once, the one declared first always wins, which keeps
+/// races reproducible at the cost of polling every future on each wake.
+///
+/// Example:
+/// ```rust
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_biased_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Stop, Reader);
+///
+/// let block = link_biased_futures!(Block, BlockFutureIdentifier;
+///     Stop => async {},
+///     Reader => async {}
+/// );
+/// let (completed_future_identifier, _) = block_on(block);
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Stop);
+/// ```
+#[macro_export]
+macro_rules! link_biased_futures {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+ $(, error = $error:ty)?
+        $(, identified_error = $identified_error:ty)? $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($one_of_block)::+] [$($identifier_enum)::+] [biased]
+                [$($error)? $(identified $identified_error)?] [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Link multiple futures returning `()` into a block resolving with the identifier of
+/// the first completed future
+///
+/// Works like [`link_futures`](macro.link_futures.html), without pairing the identifier
+/// with the output. Futures returning anything other than `()` are rejected.
+///
+/// Example:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_unit_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Stop);
+///
+/// let block = link_unit_futures!(Block, BlockFutureIdentifier;
+///     Reader => pending(),
+///     Stop => async {}
+/// );
+/// assert_eq!(block_on(block), BlockFutureIdentifier::Stop);
+/// ```
+#[macro_export]
+macro_rules! link_unit_futures {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+ $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($one_of_block)::+] [$($identifier_enum)::+] [unit] []
+                [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Link multiple futures returning `Result` into a block, which stops on the first error
+///
+/// Works like [`link_futures`](macro.link_futures.html), but successful completion of a
+/// future doesn't stop the block: the output is dropped and the rest of the futures
+/// keep running. The block resolves with the identifier and the error of the first future
+/// which failed, or with `Ok(())` once all of the futures succeed. Skipped variants are
+/// treated as already succeeded. With the `log` feature enabled, successfully completed
+/// futures are logged at the debug level. A common error type may follow the identifier
+/// name, the same way as in `link_futures`.
+///
+/// Example:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_try_futures, linked_block};
+///
+/// linked_block!(Supervisor, SupervisorFutureIdentifier; Server, Migrations, Watcher);
+///
+/// let block = link_try_futures!(Supervisor, SupervisorFutureIdentifier;
+///     Server => pending::<Result<(), String>>(),
+///     Migrations => async { Ok(3) },
+///     Watcher => async { Err::<(), _>("config removed".to_string()) }
+/// );
+/// assert_eq!(
+///     block_on(block),
+///     Err((SupervisorFutureIdentifier::Watcher, "config removed".to_string()))
+/// );
+/// ```
+#[macro_export]
+macro_rules! link_try_futures {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+ $(, error = $error:ty)?
+        $(, identified_error = $identified_error:ty)? $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($one_of_block)::+] [$($identifier_enum)::+] [try]
+                [$($error)? $(identified $identified_error)?] [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Link multiple futures returning `Result` into a block, which stops on the first success
+///
+/// Works like [`link_futures`](macro.link_futures.html), but failed futures don't stop the
+/// block. The block resolves with the identifier and the output of the first future which
+/// succeeded, or with the identifiers and the errors of all of the futures once every one
+/// of them fails, in the order of completion. Skipped variants are not linked, so they
+/// don't prevent the block from failing. A common error type may follow the identifier
+/// name, the same way as in `link_futures`.
+///
+/// Example:
+/// ```rust
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_select_ok_futures, linked_block};
+///
+/// linked_block!(Fetch, FetchFutureIdentifier; Primary, Mirror);
+///
+/// let block = link_select_ok_futures!(Fetch, FetchFutureIdentifier;
+///     Primary => async { Err::<&str, _>("timed out") },
+///     Mirror => async { Err("not found") }
+/// );
+/// let errors = block_on(block).unwrap_err();
+/// assert_eq!(errors.len(), 2);
+/// assert!(errors.contains(&(FetchFutureIdentifier::Mirror, "not found")));
+/// ```
+#[macro_export]
+macro_rules! link_select_ok_futures {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+ $(, error = $error:ty)?
+        $(, identified_error = $identified_error:ty)? $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($one_of_block)::+] [$($identifier_enum)::+] [select_ok]
+                [$($error)? $(identified $identified_error)?] [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Link multiple futures into a block, which completes once all of the futures complete
+///
+/// Works like [`link_futures`](macro.link_futures.html), but the block resolves with the
+/// outputs of all of the futures in a `BTreeMap` keyed by the identifiers. Skipped futures
+/// are missing from the outputs. A common error type may follow the identifier name, the
+/// same way as in `link_futures`.
+///
+/// Example:
+/// ```rust
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{join_futures, linked_block};
+///
+/// linked_block!(Replicas, ReplicaFutureIdentifier; Primary, Secondary, Backup);
+///
+/// let block = join_futures!(Replicas, ReplicaFutureIdentifier;
+///     Primary => async { 1 },
+///     Secondary => async { 2 },
+///     Backup => skip
+/// );
+/// let outputs = block_on(block);
+/// assert_eq!(outputs.len(), 2);
+/// assert_eq!(outputs[&ReplicaFutureIdentifier::Secondary], 2);
+/// ```
+///
+/// With a `quorum` following the identifier name, the block resolves as soon as that many
+/// futures complete, with the outputs of the futures which made the quorum. The rest of
+/// the futures are dropped with the block. If fewer futures than the quorum are linked,
+/// the block resolves with all of the outputs once they complete:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{join_futures, linked_block};
+///
+/// linked_block!(Replicas, ReplicaFutureIdentifier; Primary, Secondary, Backup);
+///
+/// let block = join_futures!(Replicas, ReplicaFutureIdentifier, quorum = 2;
+///     Primary => async { "ack" },
+///     Secondary => pending(),
+///     Backup => async { "ack" }
+/// );
+/// let acknowledged = block_on(block);
+/// assert_eq!(
+///     acknowledged.keys().copied().collect::<Vec<_>>(),
+///     [ReplicaFutureIdentifier::Primary, ReplicaFutureIdentifier::Backup]
+/// );
+/// ```
+#[macro_export]
+macro_rules! join_futures {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+
+        $(, quorum = $quorum:expr)? $(, error = $error:ty)?
+        $(, identified_error = $identified_error:ty)? $(, timer = $timer:ty)?
+        $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($one_of_block)::+] [$($identifier_enum)::+] [join $(($quorum))?]
+                [$($error)? $(identified $identified_error)?] [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Link multiple futures into a block resolving with the output enum of the block
+///
+/// Works like [`link_futures`](macro.link_futures.html), but the block has to be declared
+/// with the name of the output enum following the identifier name. The linked futures may
+/// then have different outputs, and the block resolves with the output of the first
+/// completed future wrapped in its variant of the output enum.
+///
+/// Example:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_typed_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier, BlockOutput; Reader, Counter, Stop);
+///
+/// let block = link_typed_futures!(Block, BlockFutureIdentifier, BlockOutput;
+///     Reader => pending::<String>(),
+///     Counter => pending::<usize>(),
+///     Stop => async {}
+/// );
+/// match block_on(block) {
+///     BlockOutput::Reader(line) => println!("read {}", line),
+///     BlockOutput::Counter(count) => println!("counted {}", count),
+///     BlockOutput::Stop(()) => println!("stopped"),
+/// }
+/// ```
+#[macro_export]
+macro_rules! link_typed_futures {
+    (
+        $($one_of_block:ident)::+, $($identifier_enum:ident)::+, $($output_enum:ident)::+
+        $(, timer = $timer:ty)? $(, spawner = $spawner:ty)?;
+        $($futures:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            __link_futures!(
+                [$($one_of_block)::+] [$($identifier_enum)::+] [typed $($output_enum)::+] []
+                [[$($timer)?] [$($spawner)?]];
+            )
+            [] [] [] [] $($futures)*
+        )
+    };
+}
+
+/// Handle the output of a block linked with [`link_typed_futures`](macro.link_typed_futures.html)
+///
+/// Each arm passes the value of the completed future to the handler closure. Futures which
+/// aren't handled are covered by the optional `_` arm, which is an expression:
+/// ```rust
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_typed_futures, linked_block, match_completed};
+///
+/// linked_block!(Block, BlockFutureIdentifier, BlockOutput; Reader, Writer, Stop);
+///
+/// let block = link_typed_futures!(Block, BlockFutureIdentifier, BlockOutput;
+///     Reader => async { "line".to_string() },
+///     Writer => futures::future::pending::<usize>(),
+///     Stop => futures::future::pending::<()>()
+/// );
+/// let length = match_completed!(block_on(block), BlockOutput;
+///     Reader => |line: String| line.len(),
+///     Writer => |written| written,
+///     _ => 0
+/// );
+/// assert_eq!(length, 4);
+/// ```
+#[macro_export]
+macro_rules! match_completed {
+    (
+        $output:expr, $($output_enum:ident)::+;
+        $( $key:ident => $handler:expr ),* $(, _ => $default:expr)? $(,)?
+    ) => {{
+        use $($output_enum)::+ as __LinkedFuturesOutput;
+
+        match $output {
+            $( __LinkedFuturesOutput::$key(value) => $crate::__private::call(value, $handler), )*
+            $( _ => $default, )?
+        }
+    }};
+}
+
+/// Assert that the expected future terminated the block, returning its output
+///
+/// Accepts the results of [`link_futures`](macro.link_futures.html),
+/// [`link_unit_futures`](macro.link_unit_futures.html) and
+/// [`LinkedBlock::timed`](struct.LinkedBlock.html#method.timed). Panics with both of the
+/// identifiers if another future terminated the block:
+/// ```rust,should_panic
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{expect_variant, link_futures, linked_block};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Stop);
+///
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     Reader => async {},
+///     Stop => pending()
+/// );
+/// // panics with "expected linked future `Stop` to complete, but `Reader` completed"
+/// expect_variant!(block_on(block), BlockFutureIdentifier::Stop);
+/// ```
+#[macro_export]
+macro_rules! expect_variant {
+    ( $result:expr, $expected:expr $(,)? ) => {
+        match $crate::__private::Completion::into_completion($result) {
+            (identifier, output) => {
+                let expected = $expected;
+                if identifier != expected {
+                    panic!(
+                        "expected linked future `{:?}` to complete, but `{:?}` completed",
+                        expected, identifier
+                    );
+                }
+                output
+            }
+        }
+    };
+}
+
+/// Normalizes the `Key => future` entries of the linking macros into
+/// `([cfg] (guard) Key [policy] (future))` groups and passes them to the callback macro
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linked_entries {
+    ( $callback:ident!($($args:tt)*) [ $($entries:tt)* ] [ $($keys:tt)* ] [] [] ) => {
+        $crate::$callback!($($args)* [ $($entries)* ] [ $($keys)* ])
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt [ $($cfg:tt)* ] $policy:tt
+        #[cfg $predicate:tt] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys [ $($cfg)* $predicate ] $policy $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[delay($delay:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [delay ($delay)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[startup($duration:expr, error)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [try_startup ($duration)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[startup($duration:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [startup ($duration)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[within($duration:expr, error)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [try_within ($duration)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[within($duration:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [within ($duration)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[every($period:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [every ($period)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[watch($watchdog:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [watch ($watchdog)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[schedule($schedule:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [schedule ($schedule)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[on_cancel($hooks:expr, $hook:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [on_cancel ($hooks) ($hook)]
+            $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[abortable($handles:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [abortable ($handles)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[retry(max = $max:expr, backoff = $backoff:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [retry ($max) ($backoff)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[retry(max = $max:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg
+            [retry ($max) ($crate::Backoff::none())] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[on_panic($policy:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [on_panic ($policy)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[catch_unwind] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [catch_unwind] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[circuit_breaker($breaker:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [circuit_breaker ($breaker)]
+            $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[restart(severity)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg
+            [restart_unless_fatal ($crate::__private::is_fatal)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[restart(fatal = $classify:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [restart_unless_fatal ($classify)]
+            $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[restart(max = $max:expr, within = $window:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [supervise ($max) (Some($window))]
+            $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[restart(max = $max:expr)] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [supervise ($max) (None)] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[restart] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [restart] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt $cfg:tt []
+        #[detached] $($rest:tt)*
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ] $keys $cfg [detached] $($rest)*
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) $entries:tt $keys:tt $cfg:tt $policy:tt
+        $key:ident $(if $guard:expr)? => termination_signal $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) $entries $keys $cfg $policy
+            $key $(if $guard)? => $crate::termination_signal() $(, $($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) $entries:tt $keys:tt $cfg:tt []
+        $key:ident $(if $guard:expr)? => blocking || $body:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) $entries $keys $cfg [blocking]
+            $key $(if $guard)? => || $body $(, $($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) $entries:tt $keys:tt $cfg:tt []
+        $key:ident $(if $guard:expr)? => blocking move || $body:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) $entries $keys $cfg [blocking]
+            $key $(if $guard)? => move || $body $(, $($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) $entries:tt $keys:tt $cfg:tt []
+        $key:ident $(if $guard:expr)? => thread |$stop:ident| $body:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) $entries $keys $cfg []
+            $key $(if $guard)? => $crate::LinkedThread::spawn(|$stop| $body) $(, $($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) $entries:tt $keys:tt $cfg:tt []
+        $key:ident $(if $guard:expr)? => thread move |$stop:ident| $body:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) $entries $keys $cfg []
+            $key $(if $guard)? => $crate::LinkedThread::spawn(move |$stop| $body) $(, $($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) $entries:tt $keys:tt $cfg:tt []
+        $key:ident $(if $guard:expr)? => || $body:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) $entries $keys $cfg []
+            $key $(if $guard)? => $crate::__private::build(|| $body) $(, $($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) $entries:tt $keys:tt $cfg:tt []
+        $key:ident $(if $guard:expr)? => move || $body:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) $entries $keys $cfg []
+            $key $(if $guard)? => $crate::__private::build(move || $body) $(, $($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) $entries:tt $keys:tt $cfg:tt $policy:tt
+        $key:ident $(if $guard:expr)? => optional $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) $entries $keys $cfg $policy
+            $key $(if $guard)? => $crate::__private::optional($value) $(, $($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] [ $($keys:tt)* ] [] []
+        $key:ident => skip $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ([] () $key [] [skip]) ] [ $($keys)* $key ] [] []
+            $($($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt [ $($cfg:tt)+ ] []
+        $key:ident => skip $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*) [ $($entries)* ([ $($cfg)+ ] () $key [] [skip]) ] $keys [] []
+            $($($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] [ $($keys:tt)* ] [] $policy:tt
+        $key:ident $(if $guard:expr)? => $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*)
+            [ $($entries)* ([] ($($guard)?) $key $policy ($value)) ] [ $($keys)* $key ]
+            [] [] $($($rest)*)?
+        )
+    };
+    (
+        $callback:ident!($($args:tt)*) [ $($entries:tt)* ] $keys:tt [ $($cfg:tt)+ ] $policy:tt
+        $key:ident $(if $guard:expr)? => $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__linked_entries!(
+            $callback!($($args)*)
+            [ $($entries)* ([ $($cfg)+ ] ($($guard)?) $key $policy ($value)) ] $keys
+            [] [] $($($rest)*)?
+        )
+    };
+}
+
+/// Expands to the tokens passed in the brackets if all of the identifiers are distinct,
+/// and to the error naming the first duplicate otherwise. The `@item` and `@expr` forms are
+/// used in item and expression positions
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __linked_unique {
+    ( @$position:ident ($d:tt) [$what:literal $problem:literal] [$($expansion:tt)*] ) => {
+        $($expansion)*
+    };
+    (
+        @expr ($d:tt) [$what:literal $problem:literal] [$($expansion:tt)*]
+        $first:ident $($rest:ident)*
+    ) => {{
+        macro_rules! __linked_futures_unique {
+            ($first $d($d tail:ident)*) => {
+                compile_error!(concat!($what, " `", stringify!($first), "` ", $problem))
+            };
+            ($d head:ident $d($d tail:ident)*) => {
+                __linked_futures_unique!($d($d tail)*)
+            };
+            () => {
+                $crate::__linked_unique!(@expr ($d) [$what $problem] [$($expansion)*] $($rest)*)
+            };
+        }
+        __linked_futures_unique!($($rest)*)
+    }};
+    (
+        @item ($d:tt) [$what:literal $problem:literal] [$($expansion:tt)*]
+        $first:ident $($rest:ident)*
+    ) => {
+        macro_rules! __linked_futures_unique {
+            ($first $d($d tail:ident)*) => {
+                compile_error!(concat!($what, " `", stringify!($first), "` ", $problem));
+            };
+            ($d head:ident $d($d tail:ident)*) => {
+                __linked_futures_unique! { $d($d tail)* }
+            };
+            () => {
+                $crate::__linked_unique! { @item ($d) [$what $problem] [$($expansion)*] $($rest)* }
+            };
+        }
+        __linked_futures_unique! { $($rest)* }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __link_futures {
+    (
+        [$($one_of_block:ident)::+] [$($identifier_enum:ident)::+] $mode:tt $error:tt $runtime:tt;
+        [ $($entries:tt)* ] [ $($keys:ident)* ]
+    ) => {
+        $crate::__linked_unique!(@expr ($) ["future" "is linked more than once"] [
+            $crate::__link_futures!(
+                @link [$($one_of_block)::+] [$($identifier_enum)::+] $mode $error $runtime;
+                $($entries)*
+            )
+        ] $($keys)*)
+    };
+    (
+        @link [$($one_of_block:ident)::+] [$($identifier_enum:ident)::+] [join_set] $error:tt
+        $runtime:tt;
+        $( ([ $($cfg:tt)* ] $guard:tt $key:ident $policy:tt $value:tt) )*
+    ) => {{
+        use $($identifier_enum)::+ as __LinkedFuturesIdentifier;
+
+        #[allow(dead_code)]
+        fn __linked_futures_exhaustive(identifier: __LinkedFuturesIdentifier) {
+            match identifier {
+                $( $(#[cfg $cfg])* __LinkedFuturesIdentifier::$key => {} )*
+            }
+        }
+
+        let block = stringify!($($one_of_block)::+);
+        let mut linked = $crate::LinkedJoinSet::new();
+        $(
+            $(#[cfg $cfg])*
+            if $crate::__link_futures!(@guard $guard) {
+                linked.spawn(__LinkedFuturesIdentifier::$key, $crate::__private::instrument(
+                    block,
+                    __LinkedFuturesIdentifier::$key.as_str(),
+                    async {
+                        $crate::__link_futures!(@convert [join_set] $error $runtime $key $policy $value)
+                    },
+                ));
+            }
+        )*
+        linked
+    }};
+    (
+        @link [$($parts:ident)::+] [$($identifier_enum:ident)::+] [parts] $error:tt $runtime:tt;
+        $( ([ $($cfg:tt)* ] $guard:tt $key:ident $policy:tt $value:tt) )*
+    ) => {{
+        use $($parts)::+ as __LinkedFuturesParts;
+        use $($identifier_enum)::+ as __LinkedFuturesIdentifier;
+
+        #[allow(dead_code)]
+        fn __linked_futures_exhaustive(identifier: __LinkedFuturesIdentifier) {
+            match identifier {
+                $( $(#[cfg $cfg])* __LinkedFuturesIdentifier::$key => {} )*
+            }
+        }
+
+        let mut parts = __LinkedFuturesParts::default();
+        $(
+            $(#[cfg $cfg])*
+            if $crate::__link_futures!(@guard $guard) {
+                parts.$key = Some($crate::__link_futures!(@part $error $runtime $key $policy $value));
+            }
+        )*
+        parts
+    }};
+    (
+        @link [$($one_of_block:ident)::+] [$($identifier_enum:ident)::+] $mode:tt $error:tt
+        $runtime:tt;
+        $( ([ $($cfg:tt)* ] $guard:tt $key:ident $policy:tt $value:tt) )*
+    ) => {{
+        use $($one_of_block)::+ as __LinkedFuturesOneOf;
+        use $($identifier_enum)::+ as __LinkedFuturesIdentifier;
+        $crate::__link_futures!(@use $mode);
+
+        #[allow(dead_code)]
+        fn __linked_futures_exhaustive(identifier: __LinkedFuturesIdentifier) {
+            match identifier {
+                $( $(#[cfg $cfg])* __LinkedFuturesIdentifier::$key => {} )*
+            }
+        }
+
+        let block = stringify!($($one_of_block)::+);
+        let mut linked = $crate::__link_futures!(@new $mode);
+        $(
+            $(#[cfg $cfg])*
+            if $crate::__link_futures!(@guard $guard)
+                && $crate::__link_futures!(@linked $mode $policy $value)
+            {
+                linked.push($crate::__private::Declared::new(
+                    linked.len(),
+                    __LinkedFuturesOneOf::$key($crate::__private::instrument(
+                        block,
+                        __LinkedFuturesIdentifier::$key.as_str(),
+                        async {
+                            $crate::__link_futures!(@convert $mode $error $runtime $key $policy $value)
+                        },
+                    )),
+                ));
+            }
+        )*
+        $crate::__link_futures!(@block $mode $runtime linked)
+    }};
+    ( @guard () ) => {
+        true
+    };
+    ( @part [] $runtime:tt $key:ident [] [skip] ) => {
+        $crate::__private::pending()
+    };
+    ( @part [] $runtime:tt $key:ident [] $value:tt ) => {
+        $value
+    };
+    ( @part $error:tt $runtime:tt $key:ident $policy:tt $value:tt ) => {
+        async { $crate::__link_futures!(@convert [parts] $error $runtime $key $policy $value) }
+    };
+    ( @timer [[] $spawner:tt] ) => {
+        $crate::__private::PhantomData::<$crate::__private::DefaultTimer>
+    };
+    ( @timer [[$timer:ty] $spawner:tt] ) => {
+        $crate::__private::PhantomData::<$timer>
+    };
+    ( @spawner [$timer:tt []] ) => {
+        $crate::__private::PhantomData::<$crate::__private::DefaultSpawner>
+    };
+    ( @spawner [$timer:tt [$spawner:ty]] ) => {
+        $crate::__private::PhantomData::<$spawner>
+    };
+    ( @guard ($guard:expr) ) => {
+        $guard
+    };
+    ( @linked [select_ok] [] [skip] ) => {
+        false
+    };
+    ( @linked $mode:tt $policy:tt $value:tt ) => {
+        true
+    };
+    ( @new [biased] ) => {
+        $crate::__private::Vec::new()
+    };
+    ( @new [spawned] ) => {
+        $crate::__private::Vec::new()
+    };
+    ( @new [local] ) => {
+        $crate::__private::Vec::new()
+    };
+    ( @new $mode:tt ) => {
+        $crate::FuturesUnordered::new()
+    };
+    ( @block [biased] $runtime:tt $linked:ident ) => {
+        $crate::LinkedBiasedBlock::new($linked)
+    };
+    ( @block [spawned] $runtime:tt $linked:ident ) => {
+        $crate::__private::spawn($crate::__link_futures!(@spawner $runtime), $linked)
+    };
+    ( @block [local] $runtime:tt $linked:ident ) => {
+        $crate::__private::spawn_local($crate::__link_futures!(@spawner $runtime), $linked)
+    };
+    ( @block [try] $runtime:tt $linked:ident ) => {
+        $crate::LinkedTryBlock::new($linked)
+    };
+    ( @block [join] $runtime:tt $linked:ident ) => {
+        $crate::LinkedJoinBlock::new($linked)
+    };
+    ( @block [join $quorum:tt] $runtime:tt $linked:ident ) => {
+        $crate::LinkedJoinBlock::with_quorum($linked, $quorum)
+    };
+    ( @block [select_ok] $runtime:tt $linked:ident ) => {
+        $crate::LinkedSelectOkBlock::new($linked)
+    };
+    ( @block $mode:tt $runtime:tt $linked:ident ) => {
+        $crate::LinkedBlock::new($linked)
+    };
+    ( @use [typed $($output_enum:ident)::+] ) => {
+        use $($output_enum)::+ as __LinkedFuturesOutput;
+    };
+    ( @use $mode:tt ) => {};
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [restart] $factory:tt ) => {{
+        $crate::__private::restart($factory).await;
+        $crate::__link_futures!(@output $mode $key [skip])
+    }};
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [circuit_breaker $breaker:tt] $factory:tt ) => {{
+        $crate::__private::circuit_breaker(
+            $crate::__link_futures!(@timer $runtime), $breaker, $factory
+        ).await;
+        $crate::__link_futures!(@output $mode $key [skip])
+    }};
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [blocking] $call:tt ) => {
+        $crate::__link_futures!(
+            @convert $mode $error $runtime $key [] (
+                $crate::__private::blocking($crate::__link_futures!(@spawner $runtime), $call)
+            )
+        )
+    };
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [detached] $value:tt ) => {{
+        let _ = $value.await;
+        $crate::__link_futures!(@output $mode $key [skip])
+    }};
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [every $period:tt] $tick:tt ) => {{
+        $crate::__private::every($crate::__link_futures!(@timer $runtime), $period, $tick).await;
+        $crate::__link_futures!(@output $mode $key [skip])
+    }};
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [schedule $schedule:tt] $run:tt ) => {{
+        $crate::__private::schedule($crate::__link_futures!(@timer $runtime), $schedule, $run)
+            .await;
+        $crate::__link_futures!(@output $mode $key [skip])
+    }};
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [on_cancel $hooks:tt $hook:tt] $value:tt ) => {
+        $crate::__link_futures!(
+            @convert $mode $error $runtime $key []
+            ($crate::__link_futures!(@on_cancel $runtime $hooks $key $hook $value))
+        )
+    };
+    ( @on_cancel [$timer:tt []] $hooks:tt $key:ident $hook:tt $value:tt ) => {
+        $crate::CancelHooks::register(&$hooks, __LinkedFuturesIdentifier::$key, $hook, $value)
+    };
+    ( @on_cancel [$timer:tt [$spawner:ty]] $hooks:tt $key:ident $hook:tt $value:tt ) => {
+        $crate::CancelHooks::register_on::<$spawner, _, _, _>(
+            &$hooks, __LinkedFuturesIdentifier::$key, $hook, $value
+        )
+    };
+    ( @convert [try] $error:tt $runtime:tt $key:ident [restart_unless_fatal $classify:tt] $factory:tt ) => {
+        $crate::__link_futures!(
+            @convert [try] $error $runtime $key []
+            ($crate::__private::restart_unless_fatal::<_, _, _, _, _, ()>($classify, $factory))
+        )
+    };
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [restart_unless_fatal $classify:tt] $factory:tt ) => {
+        $crate::__link_futures!(
+            @convert $mode $error $runtime $key []
+            ($crate::__private::restart_unless_fatal($classify, $factory))
+        )
+    };
+    ( @convert [try] $error:tt $runtime:tt $key:ident [supervise $max:tt $window:tt] $factory:tt ) => {
+        $crate::__link_futures!(
+            @convert [try] $error $runtime $key []
+            ($crate::__private::supervise::<_, _, _, ()>(
+                __LinkedFuturesIdentifier::$key, $max, $window, $factory
+            ))
+        )
+    };
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [supervise $max:tt $window:tt] $factory:tt ) => {
+        $crate::__link_futures!(
+            @convert $mode $error $runtime $key []
+            ($crate::__private::supervise(__LinkedFuturesIdentifier::$key, $max, $window, $factory))
+        )
+    };
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [retry $max:tt $backoff:tt] $factory:tt ) => {
+        $crate::__link_futures!(
+            @convert $mode $error $runtime $key [] (
+                $crate::__private::retry($crate::__link_futures!(@timer $runtime), $max, $backoff, $factory)
+            )
+        )
+    };
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [on_panic $policy:tt] $factory:tt ) => {
+        $crate::__link_futures!(
+            @convert $mode $error $runtime $key []
+            ($crate::__private::on_panic(__LinkedFuturesIdentifier::$key, $policy, $factory))
+        )
+    };
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [catch_unwind] $value:tt ) => {
+        $crate::__link_futures!(
+            @convert $mode $error $runtime $key []
+            ($crate::__private::catch_unwind(__LinkedFuturesIdentifier::$key, $value))
+        )
+    };
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [abortable $handles:tt] $value:tt ) => {
+        $crate::__link_futures!(
+            @convert $mode $error $runtime $key []
+            ($crate::AbortHandles::register(&$handles, __LinkedFuturesIdentifier::$key, $value))
+        )
+    };
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [watch $watchdog:tt] $value:tt ) => {
+        $crate::__link_futures!(
+            @convert $mode $error $runtime $key []
+            ($crate::__link_futures!(@watch $runtime $watchdog $key $value))
+        )
+    };
+    ( @watch [$timer:tt []] $watchdog:tt $key:ident $value:tt ) => {
+        $crate::Watchdog::watch(&$watchdog, __LinkedFuturesIdentifier::$key, $value)
+    };
+    ( @watch [$timer:tt [$spawner:ty]] $watchdog:tt $key:ident $value:tt ) => {
+        $crate::Watchdog::watch_on::<$spawner, _>(&$watchdog, __LinkedFuturesIdentifier::$key, $value)
+    };
+    ( @convert $mode:tt $error:tt $runtime:tt $key:ident [$within:ident $duration:tt] $value:tt ) => {
+        $crate::__link_futures!(
+            @convert $mode $error $runtime $key [] (
+                $crate::__private::$within($crate::__link_futures!(@timer $runtime), $value, $duration)
+            )
+        )
+    };
+    ( @convert $mode:tt [] $runtime:tt $key:ident [] $value:tt ) => {
+        $crate::__link_futures!(@output $mode $key $value)
+    };
+    ( @convert $mode:tt [identified $error:ty] $runtime:tt $key:ident [] [skip] ) => {
+        $crate::__link_futures!(@output $mode $key [skip])
+    };
+    ( @convert $mode:tt [identified $error:ty] $runtime:tt $key:ident [] $value:tt ) => {
+        $crate::__link_futures!(
+            @output $mode $key ($crate::__private::identify_err::<$error, _, _, _, _>(
+                __LinkedFuturesIdentifier::$key, $value
+            ))
+        )
+    };
+    ( @convert $mode:tt [$error:ty] $runtime:tt $key:ident [] [skip] ) => {
+        $crate::__link_futures!(@output $mode $key [skip])
+    };
+    ( @convert $mode:tt [$error:ty] $runtime:tt $key:ident [] $value:tt ) => {
+        $crate::__link_futures!(
+            @output $mode $key ($crate::__private::TryFutureExt::err_into::<$error>($value))
+        )
+    };
+    ( @output [join $($quorum:tt)?] $key:ident [skip] ) => {
+        None
+    };
+    ( @output [try] $key:ident [skip] ) => {
+        Ok(__LinkedFuturesIdentifier::$key)
+    };
+    ( @output [typed $($output_enum:ident)::+] $key:ident [skip] ) => {
+        __LinkedFuturesOutput::$key($crate::__private::pending::<()>().await)
+    };
+    ( @output $mode:tt $key:ident [skip] ) => {
+        $crate::__link_futures!(@output $mode $key ($crate::__private::pending()))
+    };
+    ( @output [biased] $key:ident $value:tt ) => {
+        $crate::__link_futures!(@output [tuple] $key $value)
+    };
+    ( @output [parts] $key:ident $value:tt ) => {
+        $value.await
+    };
+    ( @output [spawned] $key:ident $value:tt ) => {
+        $crate::__link_futures!(@output [tuple] $key $value)
+    };
+    ( @output [join_set] $key:ident $value:tt ) => {
+        $value.await
+    };
+    ( @output [local] $key:ident $value:tt ) => {
+        $crate::__link_futures!(@output [tuple] $key $value)
+    };
+    ( @output [tuple] $key:ident $value:tt ) => {
+        (__LinkedFuturesIdentifier::$key, $value.await)
+    };
+    ( @output [unit] $key:ident $value:tt ) => {{
+        let () = $value.await;
+        __LinkedFuturesIdentifier::$key
+    }};
+    ( @output [typed $($output_enum:ident)::+] $key:ident $value:tt ) => {
+        __LinkedFuturesOutput::$key($value.await)
+    };
+    ( @output [join $($quorum:tt)?] $key:ident $value:tt ) => {
+        Some((__LinkedFuturesIdentifier::$key, $value.await))
+    };
+    ( @output [select_ok] $key:ident $value:tt ) => {
+        match $value.await {
+            Ok(output) => Ok((__LinkedFuturesIdentifier::$key, output)),
+            Err(err) => Err((__LinkedFuturesIdentifier::$key, err)),
+        }
+    };
+    ( @output [try] $key:ident $value:tt ) => {
+        match $value.await {
+            Ok(_) => Ok(__LinkedFuturesIdentifier::$key),
+            Err(err) => Err((__LinkedFuturesIdentifier::$key, err)),
+        }
+    };
+}
+
+/// Declare and link multiple futures in a single step
+///
+/// Expands to the identifier enum and a `let` binding holding the linked block. The one-of
+/// type is generated inside of the binding's initializer, so several blocks may be declared
+/// in the same function without naming conflicts.
+///
+/// Example:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::linked;
+///
+/// block_on(async {
+///     linked!(let block: StopIdentifier;
+///         Never => pending::<()>(),
+///         Stop => async {}
+///     );
+///     let (completed_future_identifier, _) = block.await;
+///     assert_eq!(completed_future_identifier, StopIdentifier::Stop);
+/// });
+/// ```
+#[macro_export]
+macro_rules! linked {
+    ( let $name:ident: $identifier_enum:ident; $( $key:ident => $value:expr ),* $(,)? ) => {
+        $crate::__linked_identifier!($identifier_enum; $($key),*);
+
+        let $name = {
+            $crate::__linked_one_of!(__LinkedFuturesBlock; $($key),*);
+
+            $crate::link_futures!(__LinkedFuturesBlock, $identifier_enum; $( $key => $value ),*)
+        };
+    };
 }
 
 #[cfg(test)]
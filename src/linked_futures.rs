@@ -0,0 +1,917 @@
+use core::future::Future;
+use core::iter::FromIterator;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::sync::{Arc, Mutex};
+
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures::future::{poll_fn, BoxFuture, FusedFuture, FutureExt};
+use futures::stream::{FusedStream, FuturesUnordered, Stream, StreamExt};
+
+/// Block of futures tagged with keys, which resolves with the key and the output of the first
+/// completed future. A block without futures never resolves, so futures may be pushed into it
+/// while it is polled.
+///
+/// Unlike the blocks returned by the linking macros, the futures and their keys don't have
+/// to be known at compile time:
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::{pending, ready, Either};
+///
+/// use linked_futures::LinkedFutures;
+///
+/// let mut block = LinkedFutures::new();
+/// for port in 8000..8004 {
+///     let future = if port == 8002 {
+///         Either::Left(ready("closed"))
+///     } else {
+///         Either::Right(pending())
+///     };
+///     block.push(port, future);
+/// }
+/// assert_eq!(block.len(), 4);
+/// assert_eq!(block_on(block), (8002, "closed"));
+/// ```
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LinkedFutures<K, F, M = ()> {
+    futures: FuturesUnordered<Keyed<K, F, M>>,
+    incoming: Option<Incoming<K, F, M>>,
+    slots: Option<Arc<Mutex<Slots>>>,
+    removed: usize,
+    is_terminated: bool,
+}
+
+/// Channel of the futures pushed with the handles
+pub(crate) type Incoming<K, F, M> = (UnboundedSender<(K, M, F)>, UnboundedReceiver<(K, M, F)>);
+
+/// Slots of a block with limited capacity, shared by the block and its handles
+#[derive(Debug)]
+pub(crate) struct Slots {
+    capacity: usize,
+    used: usize,
+    wakers: Vec<Waker>,
+}
+
+impl Slots {
+    pub(crate) fn new(capacity: usize, used: usize) -> Arc<Mutex<Slots>> {
+        Arc::new(Mutex::new(Slots {
+            capacity,
+            used,
+            wakers: Vec::new(),
+        }))
+    }
+
+    pub(crate) fn acquire(slots: &Option<Arc<Mutex<Slots>>>) -> bool {
+        match slots {
+            Some(slots) => {
+                let mut slots = slots.lock().unwrap();
+                if slots.used == slots.capacity {
+                    return false;
+                }
+                slots.used += 1;
+                true
+            }
+            None => true,
+        }
+    }
+
+    pub(crate) fn release(slots: &Option<Arc<Mutex<Slots>>>) {
+        if let Some(slots) = slots {
+            let wakers = {
+                let mut slots = slots.lock().unwrap();
+                slots.used -= 1;
+                core::mem::take(&mut slots.wakers)
+            };
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Handle linking futures into a [`LinkedFutures`](struct.LinkedFutures.html) block from
+/// other tasks, while the block is polled
+///
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::{join, pending, BoxFuture};
+/// use futures::FutureExt;
+///
+/// use linked_futures::LinkedFutures;
+///
+/// let mut supervisor = LinkedFutures::<u32, BoxFuture<'static, &str>>::new();
+/// let handle = supervisor.handle();
+/// let accept = async move {
+///     assert!(handle.push(1, pending().boxed()).is_ok());
+///     assert!(handle.push(2, async { "disconnected" }.boxed()).is_ok());
+/// };
+/// let (completed, ()) = block_on(join(supervisor, accept));
+/// assert_eq!(completed, (2, "disconnected"));
+/// ```
+#[derive(Debug)]
+pub struct Handle<K, F, M = ()> {
+    sender: UnboundedSender<(K, M, F)>,
+    slots: Option<Arc<Mutex<Slots>>>,
+}
+
+impl<K, F, M> Clone for Handle<K, F, M> {
+    fn clone(&self) -> Self {
+        Handle {
+            sender: self.sender.clone(),
+            slots: self.slots.clone(),
+        }
+    }
+}
+
+impl<K, F, M: Default> Handle<K, F, M> {
+    /// Link `future` tagged with `key` into the block. Returns the key and the future back
+    /// if the block is already dropped, or if it's at its capacity.
+    pub fn push(&self, key: K, future: F) -> Result<(), (K, F)> {
+        if !Slots::acquire(&self.slots) {
+            return Err((key, future));
+        }
+        self.send(key, M::default(), future)
+            .map_err(|(key, _, future)| (key, future))
+    }
+
+    /// Link `future` tagged with `key` into the block, waiting until the block has free
+    /// capacity. Returns the key and the future back if the block is already dropped.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::{join, pending, BoxFuture};
+    /// use futures::FutureExt;
+    ///
+    /// use linked_futures::LinkedFutures;
+    ///
+    /// let mut requests = LinkedFutures::<u32, BoxFuture<'static, ()>>::with_capacity(1);
+    /// requests.push(1, pending().boxed());
+    /// let handle = requests.handle();
+    /// assert!(handle.push(2, pending().boxed()).is_err());
+    /// let accept = async {
+    ///     assert!(handle.push_ready(2, pending().boxed()).await.is_ok());
+    /// };
+    /// let cancel = async {
+    ///     assert!(requests.cancel(&1));
+    /// };
+    /// block_on(join(accept, cancel));
+    /// ```
+    pub async fn push_ready(&self, key: K, future: F) -> Result<(), (K, F)> {
+        self.acquire_ready().await;
+        self.send(key, M::default(), future)
+            .map_err(|(key, _, future)| (key, future))
+    }
+}
+
+impl<K, F, M> Handle<K, F, M> {
+    pub(crate) fn new(
+        sender: UnboundedSender<(K, M, F)>,
+        slots: Option<Arc<Mutex<Slots>>>,
+    ) -> Self {
+        Handle { sender, slots }
+    }
+
+    /// Link `future` tagged with `key` and carrying `meta` into the block. Returns them back
+    /// if the block is already dropped, or if it's at its capacity.
+    pub fn insert_with_meta(&self, key: K, meta: M, future: F) -> Result<(), (K, M, F)> {
+        if !Slots::acquire(&self.slots) {
+            return Err((key, meta, future));
+        }
+        self.send(key, meta, future)
+    }
+
+    async fn acquire_ready(&self) {
+        if let Some(slots) = &self.slots {
+            poll_fn(|cx| {
+                let mut slots = slots.lock().unwrap();
+                if slots.used == slots.capacity {
+                    slots.wakers.push(cx.waker().clone());
+                    return Poll::Pending;
+                }
+                slots.used += 1;
+                Poll::Ready(())
+            })
+            .await;
+        }
+    }
+
+    fn send(&self, key: K, meta: M, future: F) -> Result<(), (K, M, F)> {
+        self.sender
+            .unbounded_send((key, meta, future))
+            .map_err(|err| {
+                Slots::release(&self.slots);
+                err.into_inner()
+            })
+    }
+}
+
+/// Future tagged with its key and metadata, which may be removed from the block before it
+/// completes
+#[derive(Debug)]
+struct Keyed<K, F, M> {
+    key: Option<K>,
+    meta: Option<M>,
+    future: Option<F>,
+    waker: Option<Waker>,
+    is_paused: bool,
+}
+
+impl<K, F, M> Keyed<K, F, M> {
+    fn new(key: K, meta: M, future: F) -> Self {
+        Keyed {
+            key: Some(key),
+            meta: Some(meta),
+            future: Some(future),
+            waker: None,
+            is_paused: false,
+        }
+    }
+
+    fn key(&self) -> Option<&K> {
+        self.future.as_ref().and(self.key.as_ref())
+    }
+
+    fn is_linked(&self, key: &K) -> bool
+    where
+        K: PartialEq,
+    {
+        self.key() == Some(key)
+    }
+
+    /// Drop the future in place, and wake the block to reclaim the slot of the future
+    fn release(self: Pin<&mut Self>) {
+        // The future is dropped in place, without being moved out
+        let this = unsafe { self.get_unchecked_mut() };
+        this.future = None;
+        this.key = None;
+        this.meta = None;
+        if let Some(waker) = this.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Stop or resume polling the future. Wakes of a paused future are not lost, as the
+    /// future is polled once it's resumed.
+    fn set_paused(self: Pin<&mut Self>, is_paused: bool) {
+        // Only the flag is changed, the future is not moved
+        let this = unsafe { self.get_unchecked_mut() };
+        this.is_paused = is_paused;
+        if !is_paused {
+            if let Some(waker) = this.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Drop the future in place and install `future` instead, waking the block to poll it
+    fn replace(self: Pin<&mut Self>, future: F) {
+        // The old future is dropped in place, and the new one is not pinned yet
+        let this = unsafe { self.get_unchecked_mut() };
+        this.future = Some(future);
+        if let Some(waker) = this.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Move the future out, and wake the block to reclaim the slot of the future
+    fn take(self: Pin<&mut Self>) -> Option<F>
+    where
+        F: Unpin,
+    {
+        // The future is `Unpin`, so it may be moved out
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = this.future.take();
+        this.key = None;
+        this.meta = None;
+        if let Some(waker) = this.waker.take() {
+            waker.wake();
+        }
+        future
+    }
+}
+
+impl<K, F: Future, M> Future for Keyed<K, F, M> {
+    type Output = Option<(K, M, F::Output)>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The future is structurally pinned, and only dropped in place. The key and the
+        // metadata are never pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = match &mut this.future {
+            Some(future) => future,
+            None => return Poll::Ready(None),
+        };
+        if this.is_paused {
+            this.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        match unsafe { Pin::new_unchecked(future) }.poll(cx) {
+            Poll::Ready(output) => {
+                let key = this.key.take().expect("Keyed polled after completion");
+                let meta = this.meta.take().expect("Keyed polled after completion");
+                Poll::Ready(Some((key, meta, output)))
+            }
+            Poll::Pending => {
+                if !this
+                    .waker
+                    .as_ref()
+                    .is_some_and(|waker| waker.will_wake(cx.waker()))
+                {
+                    this.waker = Some(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Creates the block without futures, carrying metadata of any type
+impl<K, F, M> Default for LinkedFutures<K, F, M> {
+    fn default() -> Self {
+        LinkedFutures {
+            futures: FuturesUnordered::new(),
+            incoming: None,
+            slots: None,
+            removed: 0,
+            is_terminated: false,
+        }
+    }
+}
+
+impl<K, F> LinkedFutures<K, F> {
+    /// Create the block without futures
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the block without futures, which links at most `capacity` futures at a time,
+    /// including the futures pushed with the handles and not yet received by the block
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::default().limited(capacity)
+    }
+}
+
+impl<K, F, M: Default> LinkedFutures<K, F, M> {
+    /// Link `future` tagged with `key` into the block, with the default metadata
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block is at its capacity.
+    pub fn push(&mut self, key: K, future: F) {
+        self.insert_with_meta(key, M::default(), future);
+    }
+
+    /// Link `future` tagged with `key` into the block, with the default metadata. Returns
+    /// the key and the future back if the block is at its capacity.
+    pub fn try_push(&mut self, key: K, future: F) -> Result<(), (K, F)> {
+        self.try_insert_with_meta(key, M::default(), future)
+            .map_err(|(key, _, future)| (key, future))
+    }
+
+    /// Drop the future tagged with `key`, and link `future` under the same key, while the
+    /// rest of the futures keep running. The metadata of the replaced future is kept. The
+    /// future is just linked if there is no future tagged with `key` in the block. Returns
+    /// `true` if a future was replaced, and the key and the future back if the future would
+    /// be linked into the block at its capacity.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::{pending, ready, BoxFuture};
+    /// use futures::FutureExt;
+    ///
+    /// use linked_futures::LinkedFutures;
+    ///
+    /// let mut workers = LinkedFutures::<&str, BoxFuture<'static, u32>>::new();
+    /// workers.push("worker", pending().boxed());
+    /// workers.push("listener", pending().boxed());
+    ///
+    /// let config_version = 2;
+    /// assert!(matches!(workers.replace("worker", ready(config_version).boxed()), Ok(true)));
+    /// assert_eq!(workers.len(), 2);
+    /// assert_eq!(block_on(workers), ("worker", 2));
+    /// ```
+    pub fn replace(&mut self, key: K, future: F) -> Result<bool, (K, F)>
+    where
+        K: PartialEq,
+    {
+        match self.linked_mut(&key) {
+            Some(keyed) => {
+                keyed.replace(future);
+                Ok(true)
+            }
+            None => self.try_push(key, future).map(|()| false),
+        }
+    }
+}
+
+impl<K, F, M> LinkedFutures<K, F, M> {
+    /// Limit the number of the futures linked at a time to `capacity`, including the futures
+    /// pushed with the handles and not yet received by the block
+    pub fn limited(mut self, capacity: usize) -> Self {
+        self.slots = Some(Slots::new(capacity, self.len()));
+        self
+    }
+
+    /// Link `future` tagged with `key`, carrying `meta` into the block. The metadata is
+    /// returned with the output of the future by [`with_meta`](#method.with_meta).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block is at its capacity.
+    ///
+    /// ```rust
+    /// use std::net::SocketAddr;
+    ///
+    /// use futures::executor::block_on;
+    /// use futures::future::{pending, ready, BoxFuture};
+    /// use futures::FutureExt;
+    ///
+    /// use linked_futures::LinkedFutures;
+    ///
+    /// let mut requests = LinkedFutures::<u64, BoxFuture<'static, u16>, SocketAddr>::default();
+    /// let peer: SocketAddr = "10.0.0.1:5000".parse().unwrap();
+    /// requests.insert_with_meta(1, peer, pending().boxed());
+    /// requests.insert_with_meta(2, "10.0.0.2:5000".parse().unwrap(), ready(404).boxed());
+    /// assert_eq!(requests.meta(&1), Some(&peer));
+    ///
+    /// let (request_id, peer, status) = block_on(requests.with_meta());
+    /// assert_eq!(request_id, 2);
+    /// assert_eq!(peer.to_string(), "10.0.0.2:5000");
+    /// assert_eq!(status, 404);
+    /// ```
+    pub fn insert_with_meta(&mut self, key: K, meta: M, future: F) {
+        if self.try_insert_with_meta(key, meta, future).is_err() {
+            panic!("LinkedFutures is at its capacity");
+        }
+    }
+
+    /// Link `future` tagged with `key`, carrying `meta` into the block. Returns them back if
+    /// the block is at its capacity.
+    pub fn try_insert_with_meta(&mut self, key: K, meta: M, future: F) -> Result<(), (K, M, F)> {
+        if !Slots::acquire(&self.slots) {
+            return Err((key, meta, future));
+        }
+        self.futures.push(Keyed::new(key, meta, future));
+        Ok(())
+    }
+
+    /// Metadata of the future tagged with `key`
+    pub fn meta(&self, key: &K) -> Option<&M>
+    where
+        K: PartialEq,
+    {
+        Pin::new(&self.futures)
+            .iter_pin_ref()
+            .map(|keyed| keyed.get_ref())
+            .find(|keyed| keyed.is_linked(key))
+            .and_then(|keyed| keyed.meta.as_ref())
+    }
+
+    /// Turn the block into a stream of the keys and the outputs of all of the futures, in the
+    /// order of completion. The stream ends once all of the futures complete, unless a
+    /// [`handle`](#method.handle) is created, as the handles may link new futures.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::StreamExt;
+    ///
+    /// use linked_futures::LinkedFutures;
+    ///
+    /// let mut jobs = LinkedFutures::new();
+    /// for job in 0..3 {
+    ///     jobs.push(job, async move { job * 2 });
+    /// }
+    /// block_on(async {
+    ///     let mut completions = jobs.into_stream();
+    ///     let mut total = 0;
+    ///     while let Some((_, output)) = completions.next().await {
+    ///         total += output;
+    ///     }
+    ///     assert_eq!(total, 6);
+    /// });
+    /// ```
+    pub fn into_stream(self) -> KeyedCompletions<K, F, M> {
+        KeyedCompletions {
+            block: self,
+            is_terminated: false,
+        }
+    }
+
+    /// Resolve with the metadata of the completed future, along with its key and output
+    pub fn with_meta(self) -> WithMeta<K, F, M> {
+        WithMeta { block: self }
+    }
+
+    /// Create a handle linking futures into the block from other tasks. Once a handle is
+    /// created, the block is kept pending while there are no futures in it, waiting for the
+    /// futures pushed with the handles.
+    pub fn handle(&mut self) -> Handle<K, F, M> {
+        let (sender, _) = self.incoming.get_or_insert_with(mpsc::unbounded);
+        Handle::new(sender.clone(), self.slots.clone())
+    }
+
+    /// Drop the future tagged with `key`, so it no longer takes part in the block. Returns
+    /// `false` if there is no such future in the block.
+    pub fn cancel(&mut self, key: &K) -> bool
+    where
+        K: PartialEq,
+    {
+        match self.linked_mut(key) {
+            Some(keyed) => {
+                keyed.release();
+                self.removed += 1;
+                Slots::release(&self.slots);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop polling the future tagged with `key`, without dropping it, until it's
+    /// [resumed](#method.resume). Returns `false` if there is no such future in the block.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::{pending, BoxFuture};
+    /// use futures::{poll, FutureExt};
+    ///
+    /// use linked_futures::LinkedFutures;
+    ///
+    /// let mut pipeline = LinkedFutures::<&str, BoxFuture<'static, &str>>::new();
+    /// pipeline.push("producer", async { "produced" }.boxed());
+    /// pipeline.push("consumer", pending().boxed());
+    /// assert!(pipeline.pause(&"producer"));
+    /// block_on(async {
+    ///     assert!(poll!(&mut pipeline).is_pending());
+    ///     assert!(pipeline.resume(&"producer"));
+    ///     assert_eq!((&mut pipeline).await, ("producer", "produced"));
+    /// });
+    /// ```
+    pub fn pause(&mut self, key: &K) -> bool
+    where
+        K: PartialEq,
+    {
+        self.set_paused(key, true)
+    }
+
+    /// Resume polling the future tagged with `key`, [paused](#method.pause) before. Returns
+    /// `false` if there is no such future in the block.
+    pub fn resume(&mut self, key: &K) -> bool
+    where
+        K: PartialEq,
+    {
+        self.set_paused(key, false)
+    }
+
+    fn set_paused(&mut self, key: &K, is_paused: bool) -> bool
+    where
+        K: PartialEq,
+    {
+        match self.linked_mut(key) {
+            Some(keyed) => {
+                keyed.set_paused(is_paused);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop all of the futures with the keys matching `predicate`, while the rest of the
+    /// futures keep running. Returns the number of the dropped futures.
+    pub fn cancel_where<P>(&mut self, mut predicate: P) -> usize
+    where
+        P: FnMut(&K) -> bool,
+    {
+        self.cancel_matching(|key, _| predicate(key))
+    }
+
+    /// Drop all of the futures carrying `group` as their metadata, while the rest of the
+    /// futures keep running. Returns the number of the dropped futures.
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::{pending, ready, BoxFuture};
+    /// use futures::FutureExt;
+    ///
+    /// use linked_futures::LinkedFutures;
+    ///
+    /// let mut sessions = LinkedFutures::<u32, BoxFuture<'static, u32>, &str>::default();
+    /// sessions.insert_with_meta(1, "acme", ready(1).boxed());
+    /// sessions.insert_with_meta(2, "acme", ready(2).boxed());
+    /// sessions.insert_with_meta(3, "initech", pending().boxed());
+    /// sessions.insert_with_meta(4, "initech", ready(4).boxed());
+    ///
+    /// assert_eq!(sessions.cancel_group(&"acme"), 2);
+    /// assert_eq!(sessions.cancel_where(|session| session % 2 == 0), 1);
+    /// assert_eq!(sessions.len(), 1);
+    /// assert_eq!(sessions.keys().collect::<Vec<_>>(), [&3]);
+    /// ```
+    pub fn cancel_group(&mut self, group: &M) -> usize
+    where
+        M: PartialEq,
+    {
+        self.cancel_matching(|_, meta| meta == group)
+    }
+
+    fn cancel_matching<P>(&mut self, mut predicate: P) -> usize
+    where
+        P: FnMut(&K, &M) -> bool,
+    {
+        let mut cancelled = 0;
+        for keyed in Pin::new(&mut self.futures).iter_pin_mut() {
+            let is_matching = match (keyed.key(), &keyed.meta) {
+                (Some(key), Some(meta)) => predicate(key, meta),
+                _ => false,
+            };
+            if is_matching {
+                keyed.release();
+                cancelled += 1;
+            }
+        }
+        for _ in 0..cancelled {
+            Slots::release(&self.slots);
+        }
+        self.removed += cancelled;
+        cancelled
+    }
+
+    /// Remove the future tagged with `key` from the block, returning it back
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::{pending, ready, BoxFuture};
+    /// use futures::FutureExt;
+    ///
+    /// use linked_futures::LinkedFutures;
+    ///
+    /// let mut clients = LinkedFutures::<&str, BoxFuture<'static, &str>>::new();
+    /// clients.push("alice", pending().boxed());
+    /// clients.push("bob", ready("bob disconnected").boxed());
+    /// clients.push("carol", ready("carol disconnected").boxed());
+    ///
+    /// let bob = clients.remove(&"bob").unwrap();
+    /// assert!(clients.cancel(&"carol"));
+    /// assert!(!clients.cancel(&"carol"));
+    /// assert_eq!(clients.len(), 1);
+    /// clients.push("bob", bob);
+    /// assert_eq!(block_on(clients), ("bob", "bob disconnected"));
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<F>
+    where
+        K: PartialEq,
+        F: Unpin,
+    {
+        let future = self.linked_mut(key)?.take();
+        self.removed += 1;
+        Slots::release(&self.slots);
+        future
+    }
+
+    /// Release the slot of a completed future
+    pub(crate) fn release_slot(&self) {
+        Slots::release(&self.slots);
+    }
+
+    /// Number of the futures which are not yet completed
+    pub fn len(&self) -> usize {
+        self.futures.len() - self.removed
+    }
+
+    /// Returns `true` if there are no futures in the block
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keys of the futures which are not yet completed, in an unspecified order
+    ///
+    /// ```rust
+    /// use futures::future::{pending, BoxFuture};
+    /// use futures::FutureExt;
+    ///
+    /// use linked_futures::LinkedFutures;
+    ///
+    /// let mut block = LinkedFutures::<u32, BoxFuture<'static, ()>>::new();
+    /// for shard in 0..3 {
+    ///     block.push(shard, pending().boxed());
+    /// }
+    /// block.cancel(&1);
+    /// let mut pending_shards = block.keys().copied().collect::<Vec<_>>();
+    /// pending_shards.sort();
+    /// assert_eq!(pending_shards, [0, 2]);
+    /// ```
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        Pin::new(&self.futures)
+            .iter_pin_ref()
+            .filter_map(|keyed| keyed.get_ref().key())
+    }
+
+    fn linked_mut(&mut self, key: &K) -> Option<Pin<&mut Keyed<K, F, M>>>
+    where
+        K: PartialEq,
+    {
+        Pin::new(&mut self.futures)
+            .iter_pin_mut()
+            .find(|keyed| keyed.is_linked(key))
+    }
+}
+
+/// [`LinkedFutures`](struct.LinkedFutures.html) over boxed futures, which may be linked
+/// regardless of their types
+///
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::BoxedLinkedFutures;
+///
+/// let plugins = vec![("metrics", 0), ("auth", 1)];
+/// let mut block = BoxedLinkedFutures::from_iter_boxed(
+///     plugins
+///         .into_iter()
+///         .map(|(name, priority)| (name, async move { priority })),
+/// );
+/// block.push_boxed("listener", pending());
+/// let (name, priority) = block_on(block);
+/// assert!(name == "metrics" || name == "auth");
+/// assert!(priority <= 1);
+/// ```
+pub type BoxedLinkedFutures<K, O> = LinkedFutures<K, BoxFuture<'static, O>>;
+
+impl<K, O> LinkedFutures<K, BoxFuture<'static, O>> {
+    /// Box `future`, and link it tagged with `key` into the block
+    pub fn push_boxed<F>(&mut self, key: K, future: F)
+    where
+        F: Future<Output = O> + Send + 'static,
+    {
+        self.push(key, future.boxed());
+    }
+
+    /// Link the boxed futures, tagged with their keys
+    pub fn from_iter_boxed<I, F>(futures: I) -> Self
+    where
+        I: IntoIterator<Item = (K, F)>,
+        F: Future<Output = O> + Send + 'static,
+    {
+        let mut block = Self::new();
+        for (key, future) in futures {
+            block.push_boxed(key, future);
+        }
+        block
+    }
+}
+
+/// Link the futures tagged with their keys
+///
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::future::{pending, Either};
+///
+/// use linked_futures::LinkedFutures;
+///
+/// let shards = vec![3, 5, 8];
+/// let mut block = shards
+///     .into_iter()
+///     .map(|shard| (shard, Either::Left(pending::<&str>())))
+///     .collect::<LinkedFutures<_, _>>();
+/// block.extend(Some((13, Either::Right(async { "rebalanced" }))));
+/// assert_eq!(block.len(), 4);
+/// assert_eq!(block_on(block), (13, "rebalanced"));
+/// ```
+impl<K, F, M: Default> FromIterator<(K, F)> for LinkedFutures<K, F, M> {
+    fn from_iter<I: IntoIterator<Item = (K, F)>>(futures: I) -> Self {
+        let mut block = Self::default();
+        block.extend(futures);
+        block
+    }
+}
+
+impl<K, F, M: Default> Extend<(K, F)> for LinkedFutures<K, F, M> {
+    fn extend<I: IntoIterator<Item = (K, F)>>(&mut self, futures: I) {
+        for (key, future) in futures {
+            self.push(key, future);
+        }
+    }
+}
+
+impl<K, F: Future, M> LinkedFutures<K, F, M> {
+    /// Poll the futures for the next completed one, without terminating the block. Resolves
+    /// with `None` once there are no futures left, unless the handles may link new ones. The
+    /// slot of the completed future is not released.
+    pub(crate) fn poll_next_completed(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<(K, M, F::Output)>> {
+        if let Some((_, receiver)) = &mut self.incoming {
+            while let Poll::Ready(Some((key, meta, future))) = receiver.poll_next_unpin(cx) {
+                self.futures.push(Keyed::new(key, meta, future));
+            }
+        }
+
+        loop {
+            match self.futures.poll_next_unpin(cx) {
+                Poll::Ready(Some(Some(output))) => return Poll::Ready(Some(output)),
+                Poll::Ready(Some(None)) => self.removed -= 1,
+                Poll::Ready(None) if self.incoming.is_some() => return Poll::Pending,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    fn poll_completed(&mut self, cx: &mut Context<'_>) -> Poll<(K, M, F::Output)> {
+        assert!(!self.is_terminated, "LinkedFutures polled after completion");
+
+        match self.poll_next_completed(cx) {
+            Poll::Ready(Some(output)) => {
+                self.is_terminated = true;
+                Poll::Ready(output)
+            }
+            // None of the futures is left to terminate the block
+            Poll::Ready(None) => Poll::Pending,
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<K, F: Future, M> Future for LinkedFutures<K, F, M> {
+    type Output = (K, F::Output);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.poll_completed(cx)
+            .map(|(key, _, output)| (key, output))
+    }
+}
+
+impl<K, F: Future, M> FusedFuture for LinkedFutures<K, F, M> {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
+
+/// Future returned by [`LinkedFutures::with_meta`](struct.LinkedFutures.html#method.with_meta),
+/// which resolves with the key, the metadata and the output of the first completed future
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WithMeta<K, F, M> {
+    block: LinkedFutures<K, F, M>,
+}
+
+impl<K, F: Future, M> Future for WithMeta<K, F, M> {
+    type Output = (K, M, F::Output);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.block.poll_completed(cx)
+    }
+}
+
+impl<K, F: Future, M> FusedFuture for WithMeta<K, F, M> {
+    fn is_terminated(&self) -> bool {
+        self.block.is_terminated
+    }
+}
+
+/// Stream returned by
+/// [`LinkedFutures::into_stream`](struct.LinkedFutures.html#method.into_stream), which
+/// yields the key and the output of every linked future as it completes
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct KeyedCompletions<K, F, M = ()> {
+    block: LinkedFutures<K, F, M>,
+    is_terminated: bool,
+}
+
+impl<K, F, M> KeyedCompletions<K, F, M> {
+    /// Access the block, to link or cancel the futures while the completions are consumed
+    pub fn get_mut(&mut self) -> &mut LinkedFutures<K, F, M> {
+        &mut self.block
+    }
+}
+
+impl<K, F: Future, M> Stream for KeyedCompletions<K, F, M> {
+    type Item = (K, F::Output);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.is_terminated {
+            return Poll::Ready(None);
+        }
+
+        match self.block.poll_next_completed(cx) {
+            Poll::Ready(Some((key, _, output))) => {
+                self.block.release_slot();
+                Poll::Ready(Some((key, output)))
+            }
+            Poll::Ready(None) => {
+                self.is_terminated = true;
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<K, F: Future, M> FusedStream for KeyedCompletions<K, F, M> {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
@@ -0,0 +1,394 @@
+use core::fmt;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use futures::channel::mpsc;
+use futures::stream::{FusedStream, Stream, StreamExt};
+use futures::task::{waker_ref, ArcWake, AtomicWaker};
+
+use crate::linked_futures::{Incoming, Slots};
+use crate::Handle;
+
+/// Event yielded by [`LinkedStreams`](struct.LinkedStreams.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamEvent<K, T> {
+    /// Item yielded by the stream tagged with the key
+    Item(K, T),
+    /// The stream tagged with the key has ended, and is removed from the block
+    Ended(K),
+}
+
+/// Streams tagged with keys, merged into a stream of their items
+///
+/// The streams are linked, cancelled, paused and limited in the same way as the futures of
+/// [`LinkedFutures`](struct.LinkedFutures.html). Every stream stays in its slot until it ends,
+/// and is only polled once it's woken, so yielding an item doesn't allocate. The merged stream
+/// ends once all of the streams end, unless a [`handle`](#method.handle) is created, as the
+/// handles may link new streams.
+/// ```rust
+/// use futures::executor::block_on;
+/// use futures::stream::{self, BoxStream};
+/// use futures::StreamExt;
+///
+/// use linked_futures::{LinkedStreams, StreamEvent};
+///
+/// let mut pipelines = LinkedStreams::<&str, BoxStream<'static, u32>>::new();
+/// pipelines.push("orders", stream::iter(vec![1, 2]).boxed());
+/// pipelines.push("refunds", stream::empty().boxed());
+/// let events = block_on(pipelines.collect::<Vec<_>>());
+/// assert_eq!(events.len(), 4);
+/// assert!(events.contains(&StreamEvent::Item("orders", 2)));
+/// assert!(events.contains(&StreamEvent::Ended("refunds")));
+/// assert_eq!(events.last(), Some(&StreamEvent::Ended("orders")));
+/// ```
+#[must_use = "streams do nothing unless polled"]
+pub struct LinkedStreams<K, S, M = ()> {
+    entries: Vec<Option<Entry<K, S, M>>>,
+    vacant: Vec<usize>,
+    ready: Arc<ReadyQueue>,
+    incoming: Option<Incoming<K, S, M>>,
+    slots: Option<Arc<Mutex<Slots>>>,
+    is_terminated: bool,
+}
+
+/// Stream linked into a slot of the block
+struct Entry<K, S, M> {
+    key: K,
+    meta: M,
+    stream: S,
+    waker: Arc<EntryWaker>,
+    is_paused: bool,
+    is_woken: bool,
+}
+
+/// Indices of the woken slots, in the order they are woken
+#[derive(Default)]
+struct ReadyQueue {
+    indices: Mutex<VecDeque<usize>>,
+    waker: AtomicWaker,
+}
+
+/// Waker of the stream in a slot, which queues the slot to be polled
+struct EntryWaker {
+    index: usize,
+    is_queued: AtomicBool,
+    ready: Arc<ReadyQueue>,
+}
+
+impl ArcWake for EntryWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        if !arc_self.is_queued.swap(true, Ordering::AcqRel) {
+            arc_self
+                .ready
+                .indices
+                .lock()
+                .unwrap()
+                .push_back(arc_self.index);
+        }
+        arc_self.ready.waker.wake();
+    }
+}
+
+// The streams are `Unpin` to be polled, and the rest of the fields are never pinned
+impl<K, S, M> Unpin for LinkedStreams<K, S, M> {}
+
+impl<K: fmt::Debug, S, M> fmt::Debug for LinkedStreams<K, S, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinkedStreams")
+            .field("keys", &self.keys().collect::<Vec<_>>())
+            .field("is_terminated", &self.is_terminated)
+            .finish()
+    }
+}
+
+/// Creates the block without streams, carrying metadata of any type
+impl<K, S, M> Default for LinkedStreams<K, S, M> {
+    fn default() -> Self {
+        LinkedStreams {
+            entries: Vec::new(),
+            vacant: Vec::new(),
+            ready: Arc::new(ReadyQueue::default()),
+            incoming: None,
+            slots: None,
+            is_terminated: false,
+        }
+    }
+}
+
+impl<K, S> LinkedStreams<K, S> {
+    /// Create the block without streams
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the block without streams, which links at most `capacity` streams at a time
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::default().limited(capacity)
+    }
+}
+
+impl<K, S: Stream + Unpin, M: Default> LinkedStreams<K, S, M> {
+    /// Link `stream` tagged with `key` into the block, with the default metadata
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block is at its capacity.
+    pub fn push(&mut self, key: K, stream: S) {
+        self.insert_with_meta(key, M::default(), stream);
+    }
+}
+
+impl<K, S: Stream + Unpin, M> LinkedStreams<K, S, M> {
+    /// Link `stream` tagged with `key`, carrying `meta` into the block
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block is at its capacity.
+    pub fn insert_with_meta(&mut self, key: K, meta: M, stream: S) {
+        if !Slots::acquire(&self.slots) {
+            panic!("LinkedStreams is at its capacity");
+        }
+        self.link(key, meta, stream);
+    }
+
+    /// Remove the stream tagged with `key` from the block, returning it back
+    pub fn remove(&mut self, key: &K) -> Option<S>
+    where
+        K: PartialEq,
+    {
+        let index = self.position(key)?;
+        Some(self.release(index).stream)
+    }
+}
+
+impl<K, S, M> LinkedStreams<K, S, M> {
+    /// Limit the number of the streams linked at a time to `capacity`, including the streams
+    /// pushed with the handles and not yet received by the block
+    pub fn limited(mut self, capacity: usize) -> Self {
+        self.slots = Some(Slots::new(capacity, self.len()));
+        self
+    }
+
+    /// Create a handle linking streams into the block from other tasks
+    pub fn handle(&mut self) -> Handle<K, S, M> {
+        let (sender, _) = self.incoming.get_or_insert_with(mpsc::unbounded);
+        Handle::new(sender.clone(), self.slots.clone())
+    }
+
+    /// Drop the stream tagged with `key`, without yielding its end. Returns `false` if there
+    /// is no such stream in the block.
+    pub fn cancel(&mut self, key: &K) -> bool
+    where
+        K: PartialEq,
+    {
+        match self.position(key) {
+            Some(index) => {
+                self.release(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop all of the streams with the keys matching `predicate`. Returns the number of the
+    /// dropped streams.
+    pub fn cancel_where<P>(&mut self, mut predicate: P) -> usize
+    where
+        P: FnMut(&K) -> bool,
+    {
+        let matching = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| match entry {
+                Some(entry) if predicate(&entry.key) => Some(index),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for &index in &matching {
+            self.release(index);
+        }
+        matching.len()
+    }
+
+    /// Stop polling the stream tagged with `key` until it's resumed. Returns `false` if
+    /// there is no such stream in the block.
+    pub fn pause(&mut self, key: &K) -> bool
+    where
+        K: PartialEq,
+    {
+        match self.entry_mut(key) {
+            Some(entry) => {
+                entry.is_paused = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Resume polling the stream tagged with `key`. Returns `false` if there is no such
+    /// stream in the block.
+    pub fn resume(&mut self, key: &K) -> bool
+    where
+        K: PartialEq,
+    {
+        match self.entry_mut(key) {
+            Some(entry) => {
+                entry.is_paused = false;
+                if core::mem::take(&mut entry.is_woken) {
+                    ArcWake::wake_by_ref(&entry.waker);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Metadata of the stream tagged with `key`
+    pub fn meta(&self, key: &K) -> Option<&M>
+    where
+        K: PartialEq,
+    {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|entry| entry.key == *key)
+            .map(|entry| &entry.meta)
+    }
+
+    /// Number of the streams which are not yet ended
+    pub fn len(&self) -> usize {
+        self.entries.len() - self.vacant.len()
+    }
+
+    /// Returns `true` if there are no streams in the block
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keys of the streams which are not yet ended, in an unspecified order
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().flatten().map(|entry| &entry.key)
+    }
+
+    /// Link the stream into a vacant slot, queueing it to be polled
+    fn link(&mut self, key: K, meta: M, stream: S) {
+        let index = self.vacant.pop().unwrap_or(self.entries.len());
+        let waker = Arc::new(EntryWaker {
+            index,
+            is_queued: AtomicBool::new(false),
+            ready: self.ready.clone(),
+        });
+        ArcWake::wake_by_ref(&waker);
+        let entry = Some(Entry {
+            key,
+            meta,
+            stream,
+            waker,
+            is_paused: false,
+            is_woken: false,
+        });
+        if index == self.entries.len() {
+            self.entries.push(entry);
+        } else {
+            self.entries[index] = entry;
+        }
+    }
+
+    /// Vacate the slot at `index`, releasing its capacity
+    fn release(&mut self, index: usize) -> Entry<K, S, M> {
+        let entry = self.entries[index].take().expect("slot is linked");
+        self.vacant.push(index);
+        Slots::release(&self.slots);
+        entry
+    }
+
+    fn position(&self, key: &K) -> Option<usize>
+    where
+        K: PartialEq,
+    {
+        self.entries
+            .iter()
+            .position(|entry| matches!(entry, Some(entry) if entry.key == *key))
+    }
+
+    fn entry_mut(&mut self, key: &K) -> Option<&mut Entry<K, S, M>>
+    where
+        K: PartialEq,
+    {
+        self.entries
+            .iter_mut()
+            .flatten()
+            .find(|entry| entry.key == *key)
+    }
+}
+
+impl<K: Clone, S: Stream + Unpin, M> Stream for LinkedStreams<K, S, M> {
+    type Item = StreamEvent<K, S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.is_terminated {
+            return Poll::Ready(None);
+        }
+
+        let this = self.get_mut();
+        this.ready.waker.register(cx.waker());
+        if let Some((_, receiver)) = &mut this.incoming {
+            let mut received = Vec::new();
+            while let Poll::Ready(Some(linked)) = receiver.poll_next_unpin(cx) {
+                received.push(linked);
+            }
+            for (key, meta, stream) in received {
+                this.link(key, meta, stream);
+            }
+        }
+
+        loop {
+            let index = match this.ready.indices.lock().unwrap().pop_front() {
+                Some(index) => index,
+                None => break,
+            };
+            let entry = match this.entries.get_mut(index) {
+                Some(Some(entry)) => entry,
+                // Stale wake of a stream which is already removed
+                _ => continue,
+            };
+            entry.waker.is_queued.store(false, Ordering::Release);
+            if entry.is_paused {
+                entry.is_woken = true;
+                continue;
+            }
+            let waker = waker_ref(&entry.waker);
+            let mut entry_cx = Context::from_waker(&waker);
+            match entry.stream.poll_next_unpin(&mut entry_cx) {
+                Poll::Ready(Some(item)) => {
+                    let key = entry.key.clone();
+                    // The stream may have more items ready, so it's polled again after the
+                    // other woken streams
+                    ArcWake::wake_by_ref(&entry.waker);
+                    return Poll::Ready(Some(StreamEvent::Item(key, item)));
+                }
+                Poll::Ready(None) => {
+                    let entry = this.release(index);
+                    return Poll::Ready(Some(StreamEvent::Ended(entry.key)));
+                }
+                Poll::Pending => {}
+            }
+        }
+
+        if this.is_empty() && this.incoming.is_none() {
+            this.is_terminated = true;
+            return Poll::Ready(None);
+        }
+        Poll::Pending
+    }
+}
+
+impl<K: Clone, S: Stream + Unpin, M> FusedStream for LinkedStreams<K, S, M> {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
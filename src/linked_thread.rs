@@ -0,0 +1,189 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+
+use futures::channel::oneshot;
+use futures::future::FusedFuture;
+use futures::task::AtomicWaker;
+
+/// Flag telling the thread of [`LinkedThread`](struct.LinkedThread.html) to stop
+#[derive(Debug, Clone)]
+pub struct ThreadStop {
+    is_stopped: Arc<AtomicBool>,
+}
+
+impl ThreadStop {
+    /// Returns `true` once the thread should stop, as the future of the thread is dropped
+    pub fn is_stopped(&self) -> bool {
+        self.is_stopped.load(Ordering::SeqCst)
+    }
+}
+
+/// Exit of the thread of [`LinkedThread`](struct.LinkedThread.html), shared with its
+/// [`ThreadExited`](struct.ThreadExited.html) futures
+#[derive(Debug, Default)]
+struct Exit {
+    is_exited: AtomicBool,
+    waker: AtomicWaker,
+}
+
+/// Future resolving once the thread of the [`LinkedThread`](struct.LinkedThread.html) it's
+/// created from exits
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ThreadExited {
+    exit: Arc<Exit>,
+}
+
+impl Future for ThreadExited {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        self.exit.waker.register(cx.waker());
+        if self.exit.is_exited.load(Ordering::SeqCst) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Future of a call running on a dedicated OS thread, which resolves with the output of the
+/// call
+///
+/// Allows synchronous components, such as a blocking event loop, to be linked with async
+/// futures. Once the future is dropped, for example as another future of the block completes,
+/// the [`ThreadStop`](struct.ThreadStop.html) passed to the call is set, so the call should
+/// check the flag regularly. A panic of the call is resumed when the future is polled, and so
+/// is a panic reporting a thread which exited without the output of the call.
+///
+/// Dropping the future doesn't wait for the call to stop, as it's usually dropped on a thread
+/// of the executor: the thread is detached instead, and signals its exit once the call
+/// returns. The [`exited`](#method.exited) future resolves then, so the call's shutdown may
+/// still be awaited:
+/// ```rust
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use futures::executor::block_on;
+///
+/// use linked_futures::{link_futures, linked_block, LinkedThread, ThreadStop};
+///
+/// linked_block!(Service, ServiceFutureIdentifier; LegacyLoop, Stop);
+///
+/// fn legacy_loop(stop: ThreadStop, is_exited: Arc<AtomicBool>) {
+///     while !stop.is_stopped() {
+///         std::thread::sleep(Duration::from_millis(1));
+///     }
+///     is_exited.store(true, Ordering::SeqCst);
+/// }
+///
+/// let is_exited = Arc::new(AtomicBool::new(false));
+/// let loop_exited = is_exited.clone();
+/// let legacy_loop = LinkedThread::spawn(move |stop| legacy_loop(stop, loop_exited));
+/// let exited = legacy_loop.exited();
+/// let block = link_futures!(Service, ServiceFutureIdentifier;
+///     LegacyLoop => legacy_loop,
+///     Stop => async {}
+/// );
+/// let (completed_future_identifier, _) = block_on(block);
+/// assert_eq!(completed_future_identifier, ServiceFutureIdentifier::Stop);
+/// block_on(exited);
+/// assert!(is_exited.load(Ordering::SeqCst));
+/// ```
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LinkedThread<T> {
+    receiver: oneshot::Receiver<thread::Result<T>>,
+    stop: ThreadStop,
+    exit: Arc<Exit>,
+    is_terminated: bool,
+}
+
+impl<T: Send + 'static> LinkedThread<T> {
+    /// Run `call` on a new thread
+    pub fn spawn<C>(call: C) -> Self
+    where
+        C: FnOnce(ThreadStop) -> T + Send + 'static,
+    {
+        let stop = ThreadStop {
+            is_stopped: Arc::new(AtomicBool::new(false)),
+        };
+        let (sender, receiver) = oneshot::channel();
+        let thread_stop = stop.clone();
+        let exit = Arc::new(Exit::default());
+        let exiting = Exiting(exit.clone());
+        // The thread is never joined, so the `JoinHandle` is dropped, detaching it
+        thread::spawn(move || {
+            let _exiting = exiting;
+            let _ = sender.send(panic::catch_unwind(AssertUnwindSafe(|| call(thread_stop))));
+        });
+        LinkedThread {
+            receiver,
+            stop,
+            exit,
+            is_terminated: false,
+        }
+    }
+}
+
+impl<T> LinkedThread<T> {
+    /// Create a future resolving once the thread of this future exits
+    pub fn exited(&self) -> ThreadExited {
+        ThreadExited {
+            exit: self.exit.clone(),
+        }
+    }
+}
+
+impl<T> Future for LinkedThread<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        assert!(!self.is_terminated, "LinkedThread polled after completion");
+
+        match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(Ok(Ok(output))) => {
+                self.is_terminated = true;
+                Poll::Ready(output)
+            }
+            Poll::Ready(Ok(Err(panic))) => {
+                self.is_terminated = true;
+                panic::resume_unwind(panic)
+            }
+            // The output was never sent, as the thread is torn down before the call returns
+            Poll::Ready(Err(oneshot::Canceled)) => {
+                self.is_terminated = true;
+                panic!("thread of LinkedThread exited without completing")
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> FusedFuture for LinkedThread<T> {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
+
+impl<T> Drop for LinkedThread<T> {
+    fn drop(&mut self) {
+        self.stop.is_stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Signals the exit of the thread once the call returns or the thread unwinds
+struct Exiting(Arc<Exit>);
+
+impl Drop for Exiting {
+    fn drop(&mut self) {
+        self.0.is_exited.store(true, Ordering::SeqCst);
+        self.0.waker.wake();
+    }
+}
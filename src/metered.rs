@@ -0,0 +1,99 @@
+use core::cell::Cell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::time::Instant;
+
+thread_local! {
+    /// Names of the block and of the variant of the linked future being polled on the thread
+    static POLLED: Cell<Option<(&'static str, &'static str)>> = const { Cell::new(None) };
+}
+
+/// Linked future recording the metrics of its variant: the completions terminating the block,
+/// and the time the future ran for until it completed or was cancelled
+#[derive(Debug)]
+pub(crate) struct Metered<F> {
+    future: F,
+    block: &'static str,
+    variant: &'static str,
+    started_at: Option<Instant>,
+    is_completed: bool,
+}
+
+impl<F> Metered<F> {
+    pub(crate) fn new(block: &'static str, variant: &'static str, future: F) -> Self {
+        Metered {
+            future,
+            block,
+            variant,
+            started_at: None,
+            is_completed: false,
+        }
+    }
+
+    fn record_duration(&self, started_at: Instant, outcome: &'static str) {
+        metrics::histogram!(
+            "linked_future_duration_seconds",
+            "block" => self.block,
+            "variant" => self.variant,
+            "outcome" => outcome,
+        )
+        .record(started_at.elapsed().as_secs_f64());
+    }
+}
+
+/// Restores the names of the polled future on the thread once the inner future is polled, or
+/// panics
+struct Polling(Option<(&'static str, &'static str)>);
+
+impl Drop for Polling {
+    fn drop(&mut self) {
+        POLLED.with(|polled| polled.set(self.0));
+    }
+}
+
+impl<F: Future> Future for Metered<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The future is structurally pinned, and never moved out
+        let this = unsafe { self.get_unchecked_mut() };
+        let started_at = *this.started_at.get_or_insert_with(Instant::now);
+        let output = {
+            let _polling =
+                Polling(POLLED.with(|polled| polled.replace(Some((this.block, this.variant)))));
+            unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx)
+        };
+        if output.is_ready() {
+            this.is_completed = true;
+            metrics::counter!(
+                "linked_future_terminations_total",
+                "block" => this.block,
+                "variant" => this.variant,
+            )
+            .increment(1);
+            this.record_duration(started_at, "completed");
+        }
+        output
+    }
+}
+
+impl<F> Drop for Metered<F> {
+    fn drop(&mut self) {
+        if let (Some(started_at), false) = (self.started_at, self.is_completed) {
+            self.record_duration(started_at, "cancelled");
+        }
+    }
+}
+
+/// Count a restart of the linked future being polled on the thread
+pub(crate) fn restarted() {
+    if let Some((block, variant)) = POLLED.with(Cell::get) {
+        metrics::counter!(
+            "linked_future_restarts_total",
+            "block" => block,
+            "variant" => variant,
+        )
+        .increment(1);
+    }
+}
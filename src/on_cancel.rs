@@ -0,0 +1,459 @@
+use core::cell::Cell;
+use core::convert::Infallible;
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::{Context, Poll};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+use futures::channel::oneshot;
+use futures::future::{BoxFuture, FusedFuture, FutureExt};
+use futures::stream::{FuturesUnordered, StreamExt};
+
+use crate::{LinkedBlock, LinkedError, Spawner};
+
+type Hook<E> = Box<dyn FnOnce() -> BoxFuture<'static, Result<(), E>> + Send>;
+
+static NEXT_SCOPE: AtomicUsize = AtomicUsize::new(0);
+
+std::thread_local! {
+    /// Scope of the [`OnCancel`] polling its block on the current thread, which the hooks
+    /// registered by the futures of the block belong to
+    static SCOPE: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Output of the cleanup hooks registered in [`CancelHooks`](struct.CancelHooks.html):
+/// either `()`, or `Result<(), E>` for the hooks which may fail
+pub trait HookOutput<E> {
+    /// Convert the output into `Result`
+    fn into_result(self) -> Result<(), E>;
+}
+
+impl<E> HookOutput<E> for () {
+    fn into_result(self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+impl<E> HookOutput<E> for Result<(), E> {
+    fn into_result(self) -> Result<(), E> {
+        self
+    }
+}
+
+/// Registry of the async cleanup hooks of the linked futures, which are run when the block
+/// is terminated, before the rest of the futures are dropped
+///
+/// Hooks are registered by marking the futures with `#[on_cancel(hooks, hook)]` in the
+/// linking macros, and run by
+/// [`LinkedBlock::on_cancel`](struct.LinkedBlock.html#method.on_cancel). The hook of
+/// a future is only run if the future is still running when the block is terminated. The
+/// hooks run within the block, unless the linking macro is given a spawner with
+/// `spawner = S`, which runs them as separate tasks.
+///
+/// The registry may be shared by several blocks, and each block only runs the hooks of its own
+/// futures. A hook belongs to the block wrapped in `on_cancel` which polls its future first,
+/// so the hooks of futures polled before the block is wrapped are never run.
+///
+/// Hooks of the registry created with [`fallible`](#method.fallible) may fail with `E`, and
+/// their errors are returned with the output of the block in the
+/// [`CleanupReport`](struct.CleanupReport.html), so failures during the shutdown aren't lost:
+/// ```rust
+/// use std::io;
+///
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, CancelHooks};
+///
+/// linked_block!(Service, ServiceFutureIdentifier; Writer, Stop);
+///
+/// let hooks = CancelHooks::fallible();
+/// let block = link_futures!(Service, ServiceFutureIdentifier;
+///     #[on_cancel(hooks, || async { Err(io::Error::from(io::ErrorKind::BrokenPipe)) })]
+///     Writer => pending(),
+///     Stop => async {}
+/// );
+/// let report = block_on(block.on_cancel(&hooks, || pending()));
+/// assert_eq!(report.id, ServiceFutureIdentifier::Stop);
+/// let errors = report.cleanup_errors;
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].0, ServiceFutureIdentifier::Writer);
+/// assert_eq!(errors[0].1.kind(), io::ErrorKind::BrokenPipe);
+/// ```
+pub struct CancelHooks<I, E = Infallible> {
+    inner: Arc<Mutex<Inner<I, E>>>,
+}
+
+struct Inner<I, E> {
+    next_token: usize,
+    hooks: Vec<Registered<I, E>>,
+}
+
+struct Registered<I, E> {
+    token: usize,
+    scope: Option<usize>,
+    identifier: I,
+    hook: Hook<E>,
+}
+
+impl<I, E> Clone for CancelHooks<I, E> {
+    fn clone(&self) -> Self {
+        CancelHooks {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<I, E> Default for CancelHooks<I, E> {
+    fn default() -> Self {
+        Self::fallible()
+    }
+}
+
+impl<I: fmt::Debug, E> fmt::Debug for CancelHooks<I, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_list()
+            .entries(inner.hooks.iter().map(|registered| &registered.identifier))
+            .finish()
+    }
+}
+
+impl<I> CancelHooks<I> {
+    /// Create the registry without hooks
+    pub fn new() -> Self {
+        Self::fallible()
+    }
+}
+
+impl<I, E> CancelHooks<I, E> {
+    /// Create the registry without hooks, which may fail with `E`
+    pub fn fallible() -> Self {
+        CancelHooks {
+            inner: Arc::new(Mutex::new(Inner {
+                next_token: 0,
+                hooks: Vec::new(),
+            })),
+        }
+    }
+
+    /// Register the cleanup `hook` of `future` with `identifier` while it's running
+    pub async fn register<H, R, F>(&self, identifier: I, hook: H, future: F) -> F::Output
+    where
+        H: FnOnce() -> R + Send + 'static,
+        R: Future + Send + 'static,
+        R::Output: HookOutput<E>,
+        F: Future,
+    {
+        let hook: Hook<E> = Box::new(move || Box::pin(async { hook().await.into_result() }));
+        self.register_hook(identifier, hook, future).await
+    }
+
+    /// Register the cleanup `hook` of `future` with `identifier` while it's running, spawning
+    /// the hook as a task with the spawner `S` once the block is terminated. The hook keeps
+    /// running in the background after the grace period elapses, and a panic of the hook is
+    /// resumed by the block.
+    pub async fn register_on<S, H, R, F>(&self, identifier: I, hook: H, future: F) -> F::Output
+    where
+        S: Spawner,
+        H: FnOnce() -> R + Send + 'static,
+        R: Future + Send + 'static,
+        R::Output: HookOutput<E>,
+        E: Send + 'static,
+        F: Future,
+    {
+        let hook: Hook<E> = Box::new(move || {
+            let (sender, receiver) = oneshot::channel();
+            S::spawn(async move {
+                let output = AssertUnwindSafe(async { hook().await.into_result() })
+                    .catch_unwind()
+                    .await;
+                let _ = sender.send(output);
+            });
+            Box::pin(async {
+                match receiver.await {
+                    Ok(Ok(result)) => result,
+                    Ok(Err(panic)) => panic::resume_unwind(panic),
+                    Err(oneshot::Canceled) => panic!("cleanup hook is dropped without completing"),
+                }
+            })
+        });
+        self.register_hook(identifier, hook, future).await
+    }
+
+    async fn register_hook<F: Future>(&self, identifier: I, hook: Hook<E>, future: F) -> F::Output {
+        let token = {
+            let mut inner = self.inner.lock().unwrap();
+            let token = inner.next_token;
+            inner.next_token += 1;
+            inner.hooks.push(Registered {
+                token,
+                scope: SCOPE.with(Cell::get),
+                identifier,
+                hook,
+            });
+            token
+        };
+        let _guard = Unregister { hooks: self, token };
+
+        future.await
+    }
+
+    /// Take the hooks belonging to `scope` out, leaving the hooks of the other blocks
+    fn take(&self, scope: usize) -> Vec<(I, Hook<E>)> {
+        let mut inner = self.inner.lock().unwrap();
+        let (taken, kept) = core::mem::take(&mut inner.hooks)
+            .into_iter()
+            .partition::<Vec<_>, _>(|registered| registered.scope == Some(scope));
+        inner.hooks = kept;
+        taken
+            .into_iter()
+            .map(|registered| (registered.identifier, registered.hook))
+            .collect()
+    }
+}
+
+/// Restores the scope of the enclosing block once the block is polled, even if it panics
+struct Scoped(Option<usize>);
+
+impl Scoped {
+    fn enter(scope: usize) -> Self {
+        Scoped(SCOPE.with(|current| current.replace(Some(scope))))
+    }
+}
+
+impl Drop for Scoped {
+    fn drop(&mut self) {
+        SCOPE.with(|current| current.set(self.0));
+    }
+}
+
+/// Running cleanup hook of the future with `identifier`
+struct Running<I, E> {
+    identifier: Option<I>,
+    hook: BoxFuture<'static, Result<(), E>>,
+}
+
+// The identifier is never pinned, and the hook is boxed
+impl<I, E> Unpin for Running<I, E> {}
+
+impl<I, E> Future for Running<I, E> {
+    type Output = (I, Result<(), E>);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.hook.as_mut().poll(cx) {
+            Poll::Ready(result) => {
+                let identifier = self.identifier.take().expect("hook is completed");
+                Poll::Ready((identifier, result))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Unregisters the hook once the future completes or is dropped
+struct Unregister<'a, I, E> {
+    hooks: &'a CancelHooks<I, E>,
+    token: usize,
+}
+
+impl<I, E> Drop for Unregister<'_, I, E> {
+    fn drop(&mut self) {
+        let mut inner = self.hooks.inner.lock().unwrap();
+        inner
+            .hooks
+            .retain(|registered| registered.token != self.token);
+    }
+}
+
+/// Output of [`OnCancel`](struct.OnCancel.html): the output of the future which terminated
+/// the block, with the errors of the cleanup hooks which failed
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CleanupReport<I, T, E = Infallible> {
+    /// Identifier of the future which terminated the block
+    pub id: I,
+    /// Output of the future which terminated the block
+    pub output: T,
+    /// Errors of the cleanup hooks which failed, with the identifiers of their futures
+    pub cleanup_errors: Vec<(I, E)>,
+}
+
+impl<I, T, E> CleanupReport<I, T, E> {
+    /// The identifier and the output of the future which terminated the block, or the first
+    /// error of the cleanup hooks as [`LinkedError::Cleanup`](enum.LinkedError.html)
+    pub fn into_result(self) -> Result<(I, T), LinkedError<I, E>> {
+        match self.cleanup_errors.into_iter().next() {
+            Some((id, err)) => Err(LinkedError::Cleanup(id, err)),
+            None => Ok((self.id, self.output)),
+        }
+    }
+}
+
+/// Future returned by [`LinkedBlock::on_cancel`](struct.LinkedBlock.html#method.on_cancel),
+/// which runs the cleanup hooks of the remaining futures before dropping them
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct OnCancel<F, I, T, G, D, E = Infallible> {
+    block: Option<LinkedBlock<F>>,
+    hooks: CancelHooks<I, E>,
+    scope: usize,
+    running: FuturesUnordered<Running<I, E>>,
+    grace: Option<G>,
+    delay: Option<D>,
+    terminated_by: Option<(I, T)>,
+    cleanup_errors: Vec<(I, E)>,
+}
+
+impl<F, I, T, G, D, E> fmt::Debug for OnCancel<F, I, T, G, D, E>
+where
+    F: fmt::Debug,
+    I: fmt::Debug,
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnCancel")
+            .field("block", &self.block)
+            .field("hooks", &self.hooks)
+            .field("running", &self.running.len())
+            .field("terminated_by", &self.terminated_by)
+            .finish()
+    }
+}
+
+impl<F, I, T> LinkedBlock<F>
+where
+    F: Future<Output = (I, T)>,
+{
+    /// Once the block is terminated, run the cleanup hooks registered in `hooks` by the
+    /// futures which are still running, before they are dropped. The hooks run concurrently,
+    /// until all of them complete, or until the future returned by `grace` resolves. Resolves
+    /// with the [`CleanupReport`](struct.CleanupReport.html) holding the output of the block
+    /// and the errors of the hooks.
+    ///
+    /// ```rust
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block, CancelHooks};
+    ///
+    /// linked_block!(Service, ServiceFutureIdentifier; Writer, Stop);
+    ///
+    /// let is_flushed = Arc::new(AtomicBool::new(false));
+    /// let flushed = is_flushed.clone();
+    /// let hooks = CancelHooks::new();
+    /// let block = link_futures!(Service, ServiceFutureIdentifier;
+    ///     #[on_cancel(hooks, move || async move { flushed.store(true, Ordering::SeqCst) })]
+    ///     Writer => pending(),
+    ///     Stop => async {}
+    /// );
+    /// let (id, ()) = block_on(block.on_cancel(&hooks, || pending()))
+    ///     .into_result()
+    ///     .unwrap();
+    /// assert_eq!(id, ServiceFutureIdentifier::Stop);
+    /// assert!(is_flushed.load(Ordering::SeqCst));
+    /// ```
+    pub fn on_cancel<G, D, E>(
+        self,
+        hooks: &CancelHooks<I, E>,
+        grace: G,
+    ) -> OnCancel<F, I, T, G, D, E>
+    where
+        G: FnOnce() -> D,
+        D: Future<Output = ()>,
+    {
+        OnCancel {
+            block: Some(self),
+            hooks: hooks.clone(),
+            scope: NEXT_SCOPE.fetch_add(1, Ordering::Relaxed),
+            running: FuturesUnordered::new(),
+            grace: Some(grace),
+            delay: None,
+            terminated_by: None,
+            cleanup_errors: Vec::new(),
+        }
+    }
+}
+
+impl<F, I, T, G, D, E> Future for OnCancel<F, I, T, G, D, E>
+where
+    F: Future<Output = (I, T)>,
+    G: FnOnce() -> D,
+    D: Future<Output = ()>,
+{
+    type Output = CleanupReport<I, T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The delay is structurally pinned, and never moved out once created. The rest of
+        // the fields are never pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let block = this
+            .block
+            .as_mut()
+            .expect("OnCancel polled after completion");
+
+        if this.terminated_by.is_none() {
+            let polled = {
+                let _scoped = Scoped::enter(this.scope);
+                Pin::new(block).poll(cx)
+            };
+            match polled {
+                Poll::Ready(output) => {
+                    this.terminated_by = Some(output);
+                    this.running
+                        .extend(this.hooks.take(this.scope).into_iter().map(
+                            |(identifier, hook)| Running {
+                                identifier: Some(identifier),
+                                hook: hook(),
+                            },
+                        ));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        loop {
+            match this.running.poll_next_unpin(cx) {
+                Poll::Ready(Some((_, Ok(())))) => {}
+                Poll::Ready(Some((identifier, Err(err)))) => {
+                    this.cleanup_errors.push((identifier, err))
+                }
+                Poll::Ready(None) => break,
+                Poll::Pending => {
+                    if let Some(grace) = this.grace.take() {
+                        this.delay = Some(grace());
+                    }
+                    let delay = this.delay.as_mut().expect("grace period is started");
+                    match unsafe { Pin::new_unchecked(delay) }.poll(cx) {
+                        Poll::Ready(()) => break,
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+            }
+        }
+
+        this.block = None;
+        this.running = FuturesUnordered::new();
+        let (id, output) = this.terminated_by.take().expect("block is terminated");
+        Poll::Ready(CleanupReport {
+            id,
+            output,
+            cleanup_errors: core::mem::take(&mut this.cleanup_errors),
+        })
+    }
+}
+
+impl<F, I, T, G, D, E> FusedFuture for OnCancel<F, I, T, G, D, E>
+where
+    F: Future<Output = (I, T)>,
+    G: FnOnce() -> D,
+    D: Future<Output = ()>,
+{
+    fn is_terminated(&self) -> bool {
+        self.block.is_none()
+    }
+}
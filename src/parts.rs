@@ -0,0 +1,51 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+
+/// Future resolving with the identifier paired with the output of the wrapped future, the
+/// way the futures of [`link_futures`](macro.link_futures.html) do. The parts generated by
+/// [`linked_block`](macro.linked_block.html) wrap each future into it when they are linked.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Identified<I, F> {
+    identifier: Option<I>,
+    future: F,
+}
+
+impl<I, F> Identified<I, F> {
+    /// Pair the output of `future` with `identifier`
+    pub fn new(identifier: I, future: F) -> Self {
+        Identified {
+            identifier: Some(identifier),
+            future,
+        }
+    }
+
+    /// Consume the wrapper, returning the future
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<I, F: Future> Future for Identified<I, F> {
+    type Output = (I, F::Output);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The future is structurally pinned, the identifier is not
+        let this = unsafe { self.get_unchecked_mut() };
+        let output = futures::ready!(unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx));
+        let identifier = this
+            .identifier
+            .take()
+            .expect("Identified polled after completion");
+        Poll::Ready((identifier, output))
+    }
+}
+
+impl<I, F: Future> FusedFuture for Identified<I, F> {
+    fn is_terminated(&self) -> bool {
+        self.identifier.is_none()
+    }
+}
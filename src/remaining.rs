@@ -0,0 +1,76 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+
+use crate::LinkedBlock;
+
+/// Future returned by [`LinkedBlock::into_remaining`](struct.LinkedBlock.html#method.into_remaining),
+/// which resolves with the output of the first completed future and the rest of the futures
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Remaining<F> {
+    futures: Option<FuturesUnordered<F>>,
+}
+
+impl<F: Future> LinkedBlock<F> {
+    /// Resolve with the output of the first completed future, together with the futures which
+    /// are not completed yet, instead of dropping them. The remaining futures may be linked
+    /// into a new block with [`LinkedBlock::new`](struct.LinkedBlock.html#method.new).
+    ///
+    /// ```rust
+    /// use futures::channel::oneshot;
+    /// use futures::executor::block_on;
+    ///
+    /// use linked_futures::{link_futures, linked_block, LinkedBlock};
+    ///
+    /// linked_block!(Block, BlockFutureIdentifier; Handshake, Connection);
+    ///
+    /// let (tx, rx) = oneshot::channel();
+    /// let block = link_futures!(Block, BlockFutureIdentifier;
+    ///     Handshake => async { "handshake done" },
+    ///     Connection => async { rx.await.unwrap() }
+    /// );
+    /// let (completed, remaining) = block_on(block.into_remaining());
+    /// assert_eq!(completed, (BlockFutureIdentifier::Handshake, "handshake done"));
+    /// assert_eq!(remaining.len(), 1);
+    ///
+    /// tx.send("connection closed").unwrap();
+    /// let completed = block_on(LinkedBlock::new(remaining));
+    /// assert_eq!(completed, (BlockFutureIdentifier::Connection, "connection closed"));
+    /// ```
+    pub fn into_remaining(self) -> Remaining<F> {
+        Remaining {
+            futures: Some(self.into_inner()),
+        }
+    }
+}
+
+impl<F: Future> Future for Remaining<F> {
+    type Output = (F::Output, FuturesUnordered<F>);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let futures = self
+            .futures
+            .as_mut()
+            .expect("Remaining polled after completion");
+
+        match futures.poll_next_unpin(cx) {
+            Poll::Ready(Some(output)) => {
+                let futures = self.futures.take().expect("futures are not taken");
+                Poll::Ready((output, futures))
+            }
+            // None of the futures is left to terminate the block
+            Poll::Ready(None) => Poll::Pending,
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<F: Future> FusedFuture for Remaining<F> {
+    fn is_terminated(&self) -> bool {
+        self.futures.is_none()
+    }
+}
@@ -0,0 +1,85 @@
+use core::time::Duration;
+use std::error::Error;
+use std::fmt;
+
+/// Delays between the attempts of linked futures marked with `#[retry]`
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use linked_futures::Backoff;
+///
+/// let backoff = Backoff::exponential(Duration::from_millis(100)).with_max(Duration::from_secs(1));
+/// assert_eq!(backoff.delay(0), Duration::from_millis(100));
+/// assert_eq!(backoff.delay(2), Duration::from_millis(400));
+/// assert_eq!(backoff.delay(10), Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Backoff {
+    initial: Duration,
+    factor: u32,
+    max: Duration,
+}
+
+impl Backoff {
+    /// Retry immediately
+    pub const fn none() -> Self {
+        Self::constant(Duration::from_secs(0))
+    }
+
+    /// Wait for the same `delay` before every retry
+    pub const fn constant(delay: Duration) -> Self {
+        Backoff {
+            initial: delay,
+            factor: 1,
+            max: delay,
+        }
+    }
+
+    /// Wait for `initial` before the first retry, doubling the delay before every next one
+    pub const fn exponential(initial: Duration) -> Self {
+        Backoff {
+            initial,
+            factor: 2,
+            max: Duration::MAX,
+        }
+    }
+
+    /// Limit the delays to `max`
+    pub fn with_max(self, max: Duration) -> Self {
+        Backoff { max, ..self }
+    }
+
+    /// Delay before the retry number `retry`, starting from zero
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.factor
+            .checked_pow(retry)
+            .and_then(|factor| self.initial.checked_mul(factor))
+            .map_or(self.max, |delay| delay.min(self.max))
+    }
+}
+
+/// Error of a linked future marked with `#[retry]`, which failed after all of the attempts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exhausted<E> {
+    /// Error of the last attempt
+    pub error: E,
+    /// Number of the attempts, including the first one
+    pub attempts: u32,
+}
+
+impl<E: fmt::Display> fmt::Display for Exhausted<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "linked future failed after {} attempts: {}",
+            self.attempts, self.error
+        )
+    }
+}
+
+impl<E: Error + 'static> Error for Exhausted<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,28 @@
+use core::future::Future;
+
+/// Drive `block` to completion, blocking the current thread
+///
+/// The block is run by the minimal executor of `futures`, so examples, command line tools and
+/// tests may drive a block without a runtime. Futures relying on a runtime, such as the timers
+/// and the tasks of tokio, still need to be run within it. Requires the `executor` feature.
+/// ```rust
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block};
+///
+/// linked_block!(Cli, CliFutureIdentifier; Prompt, Command);
+///
+/// # #[cfg(feature = "executor")]
+/// # {
+/// let block = link_futures!(Cli, CliFutureIdentifier;
+///     Prompt => pending(),
+///     Command => async { 0 }
+/// );
+/// let (completed_future_identifier, exit_code) = linked_futures::run_linked(block);
+/// assert_eq!(completed_future_identifier, CliFutureIdentifier::Command);
+/// assert_eq!(exit_code, 0);
+/// # }
+/// ```
+pub fn run_linked<B: Future>(block: B) -> B::Output {
+    futures::executor::block_on(block)
+}
@@ -0,0 +1,85 @@
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+
+/// Future returned by [`link_select_ok_futures`](macro.link_select_ok_futures.html), which
+/// resolves with the first successful output, or with all of the errors once every future
+/// fails
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LinkedSelectOkBlock<F, I, E> {
+    futures: FuturesUnordered<F>,
+    errors: Vec<(I, E)>,
+    is_terminated: bool,
+}
+
+impl<F, I, T, E> LinkedSelectOkBlock<F, I, E>
+where
+    F: Future<Output = Result<(I, T), (I, E)>>,
+{
+    /// Link the futures into a block
+    pub fn new(futures: FuturesUnordered<F>) -> Self {
+        LinkedSelectOkBlock {
+            errors: Vec::with_capacity(futures.len()),
+            futures,
+            is_terminated: false,
+        }
+    }
+}
+
+impl<F, I, E> LinkedSelectOkBlock<F, I, E> {
+    /// Errors of the futures which have already failed
+    pub fn errors(&self) -> &[(I, E)] {
+        &self.errors
+    }
+
+    /// Consume the block, returning the futures which are not yet completed
+    pub fn into_inner(self) -> FuturesUnordered<F> {
+        self.futures
+    }
+}
+
+// The linked futures are pinned by `FuturesUnordered` itself, and the errors are never pinned
+impl<F, I, E> Unpin for LinkedSelectOkBlock<F, I, E> {}
+
+impl<F, I, T, E> Future for LinkedSelectOkBlock<F, I, E>
+where
+    F: Future<Output = Result<(I, T), (I, E)>>,
+{
+    type Output = Result<(I, T), Vec<(I, E)>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        assert!(
+            !self.is_terminated,
+            "LinkedSelectOkBlock polled after completion"
+        );
+
+        loop {
+            match self.futures.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(output))) => {
+                    self.is_terminated = true;
+                    return Poll::Ready(Ok(output));
+                }
+                Poll::Ready(Some(Err(err))) => self.errors.push(err),
+                Poll::Ready(None) => {
+                    self.is_terminated = true;
+                    return Poll::Ready(Err(core::mem::take(&mut self.errors)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<F, I, T, E> FusedFuture for LinkedSelectOkBlock<F, I, E>
+where
+    F: Future<Output = Result<(I, T), (I, E)>>,
+{
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
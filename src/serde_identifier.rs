@@ -0,0 +1,95 @@
+use core::fmt;
+
+use serde::de::{self, DeserializeSeed, Deserializer, EnumAccess, VariantAccess, Visitor};
+use serde::Serializer;
+
+pub fn serialize_identifier<S: Serializer>(
+    serializer: S,
+    name: &'static str,
+    index: usize,
+    variants: &'static [&'static str],
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_unit_variant(name, index as u32, variants[index])
+}
+
+pub fn deserialize_identifier<'de, D: Deserializer<'de>, T>(
+    deserializer: D,
+    name: &'static str,
+    variants: &'static [&'static str],
+    from_index: fn(usize) -> Option<T>,
+) -> Result<T, D::Error> {
+    deserializer.deserialize_enum(
+        name,
+        variants,
+        IdentifierVisitor {
+            variants,
+            from_index,
+        },
+    )
+}
+
+struct IdentifierVisitor<T> {
+    variants: &'static [&'static str],
+    from_index: fn(usize) -> Option<T>,
+}
+
+impl<'de, T> Visitor<'de> for IdentifierVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("linked future identifier")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<T, A::Error> {
+        let (index, variant) = data.variant_seed(VariantSeed {
+            variants: self.variants,
+        })?;
+        variant.unit_variant()?;
+        Ok((self.from_index)(index).expect("variant index is checked by the seed"))
+    }
+}
+
+struct VariantSeed {
+    variants: &'static [&'static str],
+}
+
+impl<'de> DeserializeSeed<'de> for VariantSeed {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for VariantSeed {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("linked future name or index")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<usize, E> {
+        if value < self.variants.len() as u64 {
+            Ok(value as usize)
+        } else {
+            Err(E::invalid_value(
+                de::Unexpected::Unsigned(value),
+                &"index of a linked future",
+            ))
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<usize, E> {
+        self.variants
+            .iter()
+            .position(|variant| *variant == value)
+            .ok_or_else(|| E::unknown_variant(value, self.variants))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<usize, E> {
+        match core::str::from_utf8(value) {
+            Ok(value) => self.visit_str(value),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Bytes(value), &self)),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::sync::{Arc, Mutex};
+
+use futures::future::FusedFuture;
+
+/// Shutdown signal broadcast to the linked futures
+///
+/// The futures subscribe to the signal by awaiting [`signalled`](#method.signalled), and
+/// should finish cleanly once it fires. The signal is triggered with
+/// [`trigger`](#method.trigger), or by the block itself when linked with
+/// [`LinkedBlock::graceful`](struct.LinkedBlock.html#method.graceful).
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    is_triggered: bool,
+    wakers: Vec<Option<Waker>>,
+}
+
+impl core::fmt::Debug for Shutdown {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Shutdown")
+            .field("is_triggered", &self.is_triggered())
+            .finish()
+    }
+}
+
+impl Shutdown {
+    /// Create the signal, which isn't triggered yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fire the signal, waking all of the subscribed futures
+    pub fn trigger(&self) {
+        let wakers = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.is_triggered = true;
+            core::mem::take(&mut inner.wakers)
+        };
+        for waker in wakers.into_iter().flatten() {
+            waker.wake();
+        }
+    }
+
+    /// Returns `true` if the signal is fired
+    pub fn is_triggered(&self) -> bool {
+        self.inner.lock().unwrap().is_triggered
+    }
+
+    /// Future resolving once the signal is fired
+    pub fn signalled(&self) -> Signalled {
+        Signalled {
+            shutdown: self.clone(),
+            key: None,
+            is_terminated: false,
+        }
+    }
+}
+
+/// Future returned by [`Shutdown::signalled`](struct.Shutdown.html#method.signalled)
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Signalled {
+    shutdown: Shutdown,
+    key: Option<usize>,
+    is_terminated: bool,
+}
+
+impl Future for Signalled {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let shutdown = self.shutdown.clone();
+        let mut inner = shutdown.inner.lock().unwrap();
+        if inner.is_triggered {
+            self.key = None;
+            self.is_terminated = true;
+            return Poll::Ready(());
+        }
+
+        let waker = Some(cx.waker().clone());
+        match self.key {
+            Some(key) => inner.wakers[key] = waker,
+            None => match inner.wakers.iter().position(Option::is_none) {
+                Some(key) => {
+                    inner.wakers[key] = waker;
+                    self.key = Some(key);
+                }
+                None => {
+                    self.key = Some(inner.wakers.len());
+                    inner.wakers.push(waker);
+                }
+            },
+        }
+        Poll::Pending
+    }
+}
+
+impl FusedFuture for Signalled {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
+
+impl Drop for Signalled {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            let mut inner = self.shutdown.inner.lock().unwrap();
+            if let Some(waker) = inner.wakers.get_mut(key) {
+                *waker = None;
+            }
+        }
+    }
+}
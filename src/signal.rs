@@ -0,0 +1,80 @@
+use std::io;
+
+/// Signal received by [`termination_signal`](fn.termination_signal.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// `SIGINT` on Unix, or ctrl-c on Windows
+    Interrupt,
+    /// `SIGTERM`, which is never received on Windows
+    Terminate,
+}
+
+/// Wait until the process receives a signal asking it to terminate: `SIGINT` or `SIGTERM`
+/// on Unix, or ctrl-c on Windows. Requires the `signals` feature, and a tokio runtime with
+/// the I/O driver enabled.
+///
+/// Blocks of services usually link it with the `termination_signal` keyword:
+/// ```rust
+/// use std::process::Command;
+/// use std::time::Duration;
+///
+/// use futures::future::{join, pending};
+///
+/// use linked_futures::{link_futures, linked_block, Signal};
+///
+/// linked_block!(Service, ServiceFutureIdentifier; Listen, Stop);
+///
+/// # #[cfg(all(unix, feature = "signals"))]
+/// #[tokio::main]
+/// async fn main() {
+///     let block = link_futures!(Service, ServiceFutureIdentifier;
+///         Listen => pending(),
+///         Stop => termination_signal
+///     );
+///     let kill = async {
+///         tokio::time::delay_for(Duration::from_millis(10)).await;
+///         let pid = std::process::id().to_string();
+///         Command::new("kill").args(&["-TERM", &pid]).status().unwrap();
+///     };
+///     let ((completed_future_identifier, signal), _) = join(block, kill).await;
+///     assert_eq!(completed_future_identifier, ServiceFutureIdentifier::Stop);
+///     assert_eq!(signal.unwrap(), Signal::Terminate);
+/// }
+/// # #[cfg(not(all(unix, feature = "signals")))]
+/// # fn main() {}
+/// ```
+pub async fn termination_signal() -> io::Result<Signal> {
+    imp::termination_signal().await
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::io;
+
+    use futures::future::{select, Either};
+    use tokio::signal::unix::{signal, SignalKind};
+
+    use super::Signal;
+
+    pub(super) async fn termination_signal() -> io::Result<Signal> {
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let mut terminate = signal(SignalKind::terminate())?;
+        let received = select(Box::pin(interrupt.recv()), Box::pin(terminate.recv())).await;
+        match received {
+            Either::Left(_) => Ok(Signal::Interrupt),
+            Either::Right(_) => Ok(Signal::Terminate),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::io;
+
+    use super::Signal;
+
+    pub(super) async fn termination_signal() -> io::Result<Signal> {
+        tokio::signal::ctrl_c().await?;
+        Ok(Signal::Interrupt)
+    }
+}
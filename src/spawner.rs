@@ -0,0 +1,202 @@
+use core::future::Future;
+
+/// Task spawner of an async runtime, used by the spawning features of the crate
+///
+/// The spawner of tokio is implemented with the `tokio` feature, and is used as
+/// [`DefaultSpawner`](type.DefaultSpawner.html). The spawners of async-std and smol are
+/// implemented with the `async-std` and `smol` features. Spawners of other runtimes may be
+/// used with [`LinkedTaskBlock::spawn_on`](struct.LinkedTaskBlock.html#method.spawn_on) and
+/// with the `spawner = S` option of the linking macros by implementing the trait:
+/// ```rust
+/// use core::future::Future;
+///
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{LinkedTaskBlock, Spawner};
+///
+/// /// Spawner running every task on its own thread
+/// struct ThreadSpawner;
+///
+/// impl Spawner for ThreadSpawner {
+///     fn spawn<F>(task: F)
+///     where
+///         F: Future<Output = ()> + Send + 'static,
+///     {
+///         std::thread::spawn(move || block_on(task));
+///     }
+/// }
+///
+/// let mut block = LinkedTaskBlock::new();
+/// block.spawn_on::<ThreadSpawner, _>(pending());
+/// block.spawn_on::<ThreadSpawner, _>(async { "stopped" });
+/// assert_eq!(block_on(block), "stopped");
+/// ```
+pub trait Spawner {
+    /// Spawn `task` detached, running it to completion in the background
+    fn spawn<F>(task: F)
+    where
+        F: Future<Output = ()> + Send + 'static;
+
+    /// Run `call` doing blocking work in the background, on a separate thread by default
+    fn spawn_blocking<C>(call: C)
+    where
+        C: FnOnce() + Send + 'static,
+    {
+        std::thread::spawn(call);
+    }
+}
+
+/// Task spawner of an async runtime running tasks on the current thread, which don't have to
+/// be `Send`
+///
+/// Used by [`LinkedTaskBlock::spawn_local_on`](struct.LinkedTaskBlock.html#method.spawn_local_on)
+/// and [`link_local_futures`](macro.link_local_futures.html). The spawner of tokio spawns the
+/// tasks into the current `LocalSet`.
+pub trait LocalSpawner {
+    /// Spawn `task` detached on the current thread, running it to completion in the background
+    fn spawn_local<F>(task: F)
+    where
+        F: Future<Output = ()> + 'static;
+}
+
+/// Spawner of tokio
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy)]
+pub struct TokioSpawner;
+
+#[cfg(feature = "tokio")]
+impl Spawner for TokioSpawner {
+    fn spawn<F>(task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(task);
+    }
+
+    fn spawn_blocking<C>(call: C)
+    where
+        C: FnOnce() + Send + 'static,
+    {
+        tokio::task::spawn_blocking(call);
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl LocalSpawner for TokioSpawner {
+    fn spawn_local<F>(task: F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        tokio::task::spawn_local(task);
+    }
+}
+
+/// Spawner of async-std. Requires the `async-std` feature.
+/// ```rust
+/// use futures::future::pending;
+///
+/// use linked_futures::{AsyncStdSpawner, LinkedTaskBlock};
+///
+/// let mut block = LinkedTaskBlock::new();
+/// block.spawn_on::<AsyncStdSpawner, _>(pending());
+/// block.spawn_on::<AsyncStdSpawner, _>(async { "stopped" });
+/// assert_eq!(async_std::task::block_on(block), "stopped");
+/// ```
+#[cfg(feature = "async-std")]
+#[derive(Debug, Clone, Copy)]
+pub struct AsyncStdSpawner;
+
+#[cfg(feature = "async-std")]
+impl Spawner for AsyncStdSpawner {
+    fn spawn<F>(task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        async_std::task::spawn(task);
+    }
+
+    fn spawn_blocking<C>(call: C)
+    where
+        C: FnOnce() + Send + 'static,
+    {
+        async_std::task::spawn_blocking(call);
+    }
+}
+
+/// Spawner of smol, running the tasks on its global executor and the blocking calls on its
+/// thread pool. Requires the `smol` feature.
+/// ```rust
+/// use futures::future::pending;
+///
+/// use linked_futures::{LinkedTaskBlock, SmolSpawner};
+///
+/// let mut block = LinkedTaskBlock::new();
+/// block.spawn_on::<SmolSpawner, _>(pending());
+/// block.spawn_on::<SmolSpawner, _>(async { "stopped" });
+/// assert_eq!(smol::block_on(block), "stopped");
+/// ```
+#[cfg(feature = "smol")]
+#[derive(Debug, Clone, Copy)]
+pub struct SmolSpawner;
+
+#[cfg(feature = "smol")]
+impl Spawner for SmolSpawner {
+    fn spawn<F>(task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        smol::spawn(task).detach();
+    }
+
+    fn spawn_blocking<C>(call: C)
+    where
+        C: FnOnce() + Send + 'static,
+    {
+        smol::spawn(smol::unblock(call)).detach();
+    }
+}
+
+/// Spawner of `wasm-bindgen-futures`, running the tasks on the event loop of the browser or of
+/// a web worker
+///
+/// The tasks run on the current thread, so the spawner is a
+/// [`LocalSpawner`](trait.LocalSpawner.html) as well, spawning the futures which are not
+/// `Send`, such as the ones of `fetch`. There are no threads on `wasm32-unknown-unknown`, so
+/// the blocking calls are run in place, blocking the event loop until they return. Requires
+/// the `wasm` feature.
+#[cfg(feature = "wasm")]
+#[derive(Debug, Clone, Copy)]
+pub struct WasmSpawner;
+
+#[cfg(feature = "wasm")]
+impl Spawner for WasmSpawner {
+    fn spawn<F>(task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        wasm_bindgen_futures::spawn_local(task);
+    }
+
+    fn spawn_blocking<C>(call: C)
+    where
+        C: FnOnce() + Send + 'static,
+    {
+        call();
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl LocalSpawner for WasmSpawner {
+    fn spawn_local<F>(task: F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        wasm_bindgen_futures::spawn_local(task);
+    }
+}
+
+/// Spawner used by the spawning features of the linking macros and
+/// [`LinkedTaskBlock`](struct.LinkedTaskBlock.html)
+#[cfg(feature = "tokio")]
+pub type DefaultSpawner = TokioSpawner;
@@ -0,0 +1,69 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{TimedOut, Timer};
+
+/// Handle passed to the futures linked with `#[startup(duration)]`, which marks the end
+/// of their startup
+#[derive(Debug, Clone)]
+pub struct Startup {
+    is_complete: Arc<AtomicBool>,
+}
+
+impl Startup {
+    /// Mark the startup as complete, so the future keeps running after the startup timeout
+    pub fn complete(&self) {
+        self.is_complete.store(true, Ordering::Release);
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct StartupTimeout<F, D> {
+    future: F,
+    delay: Option<D>,
+    is_complete: Arc<AtomicBool>,
+}
+
+impl<F: Future, D> StartupTimeout<F, D> {
+    pub(crate) fn new<T>(duration: Duration, start: impl FnOnce(Startup) -> F) -> Self
+    where
+        T: Timer<Delay = D>,
+    {
+        let is_complete = Arc::new(AtomicBool::new(false));
+        StartupTimeout {
+            future: start(Startup {
+                is_complete: is_complete.clone(),
+            }),
+            delay: Some(T::delay_for(duration)),
+            is_complete,
+        }
+    }
+}
+
+impl<F: Future, D: Future<Output = ()>> Future for StartupTimeout<F, D> {
+    type Output = Result<F::Output, TimedOut>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The future and the delay are structurally pinned. The delay is dropped in place
+        // once the startup is complete.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        if let Poll::Ready(output) = unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        if this.is_complete.load(Ordering::Acquire) {
+            this.delay = None;
+        }
+        if let Some(delay) = &mut this.delay {
+            if let Poll::Ready(()) = unsafe { Pin::new_unchecked(delay) }.poll(cx) {
+                return Poll::Ready(Err(TimedOut));
+            }
+        }
+        Poll::Pending
+    }
+}
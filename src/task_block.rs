@@ -0,0 +1,264 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+use futures::channel::oneshot;
+use futures::future::{AbortHandle, Abortable, Aborted, FusedFuture, FutureExt};
+use futures::stream::{FuturesUnordered, StreamExt};
+
+#[cfg(feature = "tokio")]
+use crate::DefaultSpawner;
+use crate::{LocalSpawner, Spawner};
+
+type Task<T> = oneshot::Receiver<thread::Result<Result<T, Aborted>>>;
+
+/// Block of linked futures spawned as separate tasks, which resolves with the output of the
+/// first completed task. A block without tasks, or with every task dropped by the runtime
+/// without completing, never resolves.
+///
+/// The block is a scope owning the spawned tasks: once a task completes, the rest of them are
+/// aborted, and dropping the block aborts all of the running tasks, so none of them outlive
+/// it. Aborting doesn't wait for the futures of the tasks to be dropped, as they are dropped
+/// by the runtime; [`shutdown`](#method.shutdown) aborts the tasks and waits until they are
+/// cancelled. A panic of a task is resumed by the block.
+///
+/// The tasks are spawned with the [`DefaultSpawner`](type.DefaultSpawner.html), or with any
+/// other [`Spawner`](trait.Spawner.html) using [`spawn_on`](#method.spawn_on), and the local
+/// tasks with any [`LocalSpawner`](trait.LocalSpawner.html) using
+/// [`spawn_local_on`](#method.spawn_local_on).
+/// ```rust
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+///
+/// use futures::future::pending;
+///
+/// use linked_futures::LinkedTaskBlock;
+///
+/// struct Cancelled(Arc<AtomicBool>);
+///
+/// impl Drop for Cancelled {
+///     fn drop(&mut self) {
+///         self.0.store(true, Ordering::SeqCst);
+///     }
+/// }
+///
+/// # #[cfg(feature = "tokio")]
+/// #[tokio::main]
+/// async fn main() {
+///     let is_cancelled = Arc::new(AtomicBool::new(false));
+///     let cancelled = Cancelled(is_cancelled.clone());
+///
+///     let mut block = LinkedTaskBlock::new();
+///     block.spawn(async move {
+///         let _cancelled = cancelled;
+///         pending::<&str>().await
+///     });
+///     block.spawn(async { "stopped" });
+///     assert_eq!((&mut block).await, "stopped");
+///
+///     block.shutdown().await;
+///     assert!(is_cancelled.load(Ordering::SeqCst));
+/// }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LinkedTaskBlock<T> {
+    tasks: FuturesUnordered<Task<T>>,
+    handles: Vec<AbortHandle>,
+    is_terminated: bool,
+}
+
+impl<T> Default for LinkedTaskBlock<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LinkedTaskBlock<T> {
+    /// Create the block without tasks
+    pub fn new() -> Self {
+        LinkedTaskBlock {
+            tasks: FuturesUnordered::new(),
+            handles: Vec::new(),
+            is_terminated: false,
+        }
+    }
+
+    /// Number of the tasks which are not yet completed or cancelled
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Returns `true` if there are no tasks in the block
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Abort all of the running tasks, without waiting until they are cancelled
+    pub fn abort(&self) {
+        for handle in &self.handles {
+            handle.abort();
+        }
+    }
+
+    /// Abort all of the running tasks, and wait until their futures are dropped
+    pub async fn shutdown(mut self) {
+        self.abort();
+        while let Some(result) = self.tasks.next().await {
+            if let Ok(Err(panic)) = result {
+                panic::resume_unwind(panic);
+            }
+        }
+    }
+
+    fn link<F>(&mut self, future: F) -> impl Future<Output = ()>
+    where
+        F: Future<Output = T>,
+    {
+        let (handle, registration) = AbortHandle::new_pair();
+        let (sender, receiver) = oneshot::channel();
+        self.tasks.push(receiver);
+        self.handles.push(handle);
+        // The future is dropped before its result is sent, so `shutdown` waits until the
+        // futures of the aborted tasks are dropped
+        AssertUnwindSafe(Abortable::new(future, registration))
+            .catch_unwind()
+            .map(move |result| {
+                let _ = sender.send(result);
+            })
+    }
+}
+
+impl<T: Send + 'static> LinkedTaskBlock<T> {
+    /// Spawn `future` as a task linked into the block with the
+    /// [`DefaultSpawner`](type.DefaultSpawner.html). Must be called from the context of a
+    /// tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub fn spawn<F>(&mut self, future: F)
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        self.spawn_on::<DefaultSpawner, F>(future);
+    }
+
+    /// Spawn `future` as a task linked into the block with the spawner `S`
+    pub fn spawn_on<S, F>(&mut self, future: F)
+    where
+        S: Spawner,
+        F: Future<Output = T> + Send + 'static,
+    {
+        S::spawn(self.link(future));
+    }
+}
+
+impl<T: 'static> LinkedTaskBlock<T> {
+    /// Spawn `future`, which doesn't have to be `Send`, as a local task linked into the block.
+    /// Must be called from the context of a tokio `LocalSet`.
+    ///
+    /// ```rust
+    /// use std::rc::Rc;
+    ///
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::LinkedTaskBlock;
+    ///
+    /// # #[cfg(feature = "tokio")]
+    /// #[tokio::main(basic_scheduler)]
+    /// async fn main() {
+    ///     let state = Rc::new("local state");
+    ///     let local = tokio::task::LocalSet::new();
+    ///     let output = local
+    ///         .run_until(async move {
+    ///             let mut block = LinkedTaskBlock::new();
+    ///             block.spawn_local(pending());
+    ///             block.spawn_local(async move { *state });
+    ///             block.await
+    ///         })
+    ///         .await;
+    ///     assert_eq!(output, "local state");
+    /// }
+    /// # #[cfg(not(feature = "tokio"))]
+    /// # fn main() {}
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub fn spawn_local<F>(&mut self, future: F)
+    where
+        F: Future<Output = T> + 'static,
+    {
+        self.spawn_local_on::<DefaultSpawner, F>(future);
+    }
+
+    /// Spawn `future`, which doesn't have to be `Send`, as a local task linked into the block
+    /// with the local spawner `S`
+    pub fn spawn_local_on<S, F>(&mut self, future: F)
+    where
+        S: LocalSpawner,
+        F: Future<Output = T> + 'static,
+    {
+        S::spawn_local(self.link(future));
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<F> From<Vec<F>> for LinkedTaskBlock<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    /// Spawn every future as a task linked into the block. Must be called from the context
+    /// of a tokio runtime.
+    fn from(futures: Vec<F>) -> Self {
+        let mut block = LinkedTaskBlock::new();
+        for future in futures {
+            block.spawn(future);
+        }
+        block
+    }
+}
+
+impl<T> Future for LinkedTaskBlock<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        assert!(
+            !self.is_terminated,
+            "LinkedTaskBlock polled after completion"
+        );
+
+        loop {
+            match self.tasks.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(Ok(Ok(output))))) => {
+                    self.is_terminated = true;
+                    self.abort();
+                    return Poll::Ready(output);
+                }
+                Poll::Ready(Some(Ok(Ok(Err(Aborted))))) => {}
+                Poll::Ready(Some(Ok(Err(panic)))) => {
+                    self.abort();
+                    panic::resume_unwind(panic);
+                }
+                // The task is dropped by the runtime without completing
+                Poll::Ready(Some(Err(oneshot::Canceled))) => {}
+                // None of the tasks is left to terminate the block
+                Poll::Ready(None) => return Poll::Pending,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<T> FusedFuture for LinkedTaskBlock<T> {
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
+
+impl<T> Drop for LinkedTaskBlock<T> {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
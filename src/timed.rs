@@ -0,0 +1,181 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::future::FusedFuture;
+
+use crate::LinkedBlock;
+
+/// Report of the linked block termination, returned by [`Timed`](struct.Timed.html)
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Terminated<I, T> {
+    /// Identifier of the future which terminated the block
+    pub id: I,
+    /// Output of the future which terminated the block
+    pub output: T,
+    /// Time at which the block was first polled
+    pub started_at: Instant,
+    /// Time the block was running for
+    pub elapsed: Duration,
+}
+
+/// Future returned by [`LinkedBlock::timed`](struct.LinkedBlock.html#method.timed), which
+/// resolves with the termination report of the block
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Timed<F> {
+    block: LinkedBlock<F>,
+    uptime: Uptime,
+}
+
+/// Handle reporting the running time of a [`Timed`](struct.Timed.html) block, which stays
+/// available after the block is moved into a task
+#[derive(Debug, Clone, Default)]
+pub struct Uptime {
+    times: Arc<Mutex<Times>>,
+}
+
+#[derive(Debug, Default)]
+struct Times {
+    started_at: Option<Instant>,
+    terminated_at: Option<Instant>,
+}
+
+impl Uptime {
+    /// Time at which the block was first polled, or `None` if it isn't polled yet
+    pub fn started_at(&self) -> Option<Instant> {
+        self.times.lock().unwrap().started_at
+    }
+
+    /// Time the block is running for, or was running for if it's already terminated.
+    /// `None` if the block isn't polled yet.
+    pub fn elapsed(&self) -> Option<Duration> {
+        let times = self.times.lock().unwrap();
+        let started_at = times.started_at?;
+        Some(match times.terminated_at {
+            Some(terminated_at) => terminated_at - started_at,
+            None => started_at.elapsed(),
+        })
+    }
+
+    /// Returns `true` if the block is polled, but not terminated yet
+    pub fn is_running(&self) -> bool {
+        let times = self.times.lock().unwrap();
+        times.started_at.is_some() && times.terminated_at.is_none()
+    }
+
+    fn start(&self) -> Instant {
+        *self
+            .times
+            .lock()
+            .unwrap()
+            .started_at
+            .get_or_insert_with(Instant::now)
+    }
+
+    fn terminate(&self) -> Instant {
+        *self
+            .times
+            .lock()
+            .unwrap()
+            .terminated_at
+            .get_or_insert_with(Instant::now)
+    }
+}
+
+impl<F, I, T> LinkedBlock<F>
+where
+    F: Future<Output = (I, T)>,
+{
+    /// Measure the running time of the block, resolving with the termination report
+    /// instead of the identifier and the output
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block};
+    ///
+    /// linked_block!(Block, BlockFutureIdentifier; Never, Stop);
+    ///
+    /// let block = link_futures!(Block, BlockFutureIdentifier;
+    ///     Never => pending::<()>(),
+    ///     Stop => async {}
+    /// );
+    /// let terminated = block_on(block.timed());
+    /// assert_eq!(terminated.id, BlockFutureIdentifier::Stop);
+    /// println!("the block was running for {:?}", terminated.elapsed);
+    /// ```
+    pub fn timed(self) -> Timed<F> {
+        Timed {
+            block: self,
+            uptime: Uptime::default(),
+        }
+    }
+}
+
+impl<F> Timed<F> {
+    /// Handle reporting the running time of the block while it's running
+    ///
+    /// ```rust
+    /// use futures::channel::oneshot;
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block};
+    ///
+    /// linked_block!(Block, BlockFutureIdentifier; Never, Stop);
+    ///
+    /// let (tx, rx) = oneshot::channel();
+    /// let block = link_futures!(Block, BlockFutureIdentifier;
+    ///     Never => pending(),
+    ///     Stop => async { rx.await.unwrap() }
+    /// )
+    /// .timed();
+    /// let uptime = block.uptime();
+    /// assert_eq!(uptime.elapsed(), None);
+    ///
+    /// let health = async {
+    ///     assert!(uptime.is_running());
+    ///     tx.send(()).unwrap();
+    /// };
+    /// let (terminated, ()) = block_on(futures::future::join(block, health));
+    /// assert!(!uptime.is_running());
+    /// assert_eq!(uptime.elapsed(), Some(terminated.elapsed));
+    /// ```
+    pub fn uptime(&self) -> Uptime {
+        self.uptime.clone()
+    }
+}
+
+impl<F, I, T> Future for Timed<F>
+where
+    F: Future<Output = (I, T)>,
+{
+    type Output = Terminated<I, T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let started_at = self.uptime.start();
+
+        match Pin::new(&mut self.block).poll(cx) {
+            Poll::Ready((id, output)) => Poll::Ready(Terminated {
+                id,
+                output,
+                started_at,
+                elapsed: self.uptime.terminate() - started_at,
+            }),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<F, I, T> FusedFuture for Timed<F>
+where
+    F: Future<Output = (I, T)>,
+{
+    fn is_terminated(&self) -> bool {
+        self.block.is_terminated()
+    }
+}
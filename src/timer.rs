@@ -0,0 +1,306 @@
+use core::cmp::{Ordering, Reverse};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Condvar, Mutex, Once, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Timer of an async runtime, used by the time-based features of the crate
+///
+/// The timer of tokio is implemented with the `tokio` feature, and is used as
+/// [`DefaultTimer`](type.DefaultTimer.html). [`ThreadTimer`](struct.ThreadTimer.html) and
+/// [`FuturesTimer`](struct.FuturesTimer.html) don't depend on a runtime. Timers may be given to the linking macros with `timer = T`, and
+/// timers of other runtimes may be used with
+/// [`LinkedBlockExt::with_timeout`](trait.LinkedBlockExt.html#method.with_timeout) and
+/// [`LinkedBlockExt::with_deadline_on`](trait.LinkedBlockExt.html#method.with_deadline_on)
+/// by implementing the trait:
+/// ```rust
+/// use std::time::{Duration, Instant};
+///
+/// use futures::executor::block_on;
+/// use futures::future::{pending, ready, Ready};
+///
+/// use linked_futures::{link_futures, linked_block, LinkedBlockExt, TimedOut, Timer};
+///
+/// /// Timer which never waits, for tests
+/// struct ImmediateTimer;
+///
+/// impl Timer for ImmediateTimer {
+///     type Delay = Ready<()>;
+///
+///     fn delay_until(_deadline: Instant) -> Self::Delay {
+///         ready(())
+///     }
+/// }
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Writer);
+///
+/// let block = link_futures!(Block, BlockFutureIdentifier;
+///     Reader => pending::<()>(),
+///     Writer => pending::<()>()
+/// );
+/// let deadline = Instant::now() + Duration::from_secs(60);
+/// let result = block_on(block.with_deadline_on::<ImmediateTimer>(deadline));
+/// assert_eq!(result, Err(TimedOut));
+/// ```
+pub trait Timer {
+    /// Future returned by the timer, which resolves once the deadline is reached
+    type Delay: Future<Output = ()>;
+
+    /// Create the future resolving at `deadline`
+    fn delay_until(deadline: Instant) -> Self::Delay;
+
+    /// Create the future resolving after `duration`
+    fn delay_for(duration: Duration) -> Self::Delay {
+        Self::delay_until(Instant::now() + duration)
+    }
+}
+
+/// Timer of tokio
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio")]
+impl Timer for TokioTimer {
+    type Delay = tokio::time::Delay;
+
+    fn delay_until(deadline: Instant) -> Self::Delay {
+        tokio::time::delay_until(deadline.into())
+    }
+
+    fn delay_for(duration: Duration) -> Self::Delay {
+        tokio::time::delay_for(duration)
+    }
+}
+
+/// Timer of `gloo-timers`, backed by `setTimeout` of the browser or of a web worker
+///
+/// `std::time::Instant` is not available on `wasm32-unknown-unknown`, so only the features
+/// taking a duration, such as
+/// [`LinkedBlockExt::with_timeout`](trait.LinkedBlockExt.html#method.with_timeout), may be
+/// used with the timer there. The delays are rounded up to whole milliseconds. Requires the
+/// `wasm` feature.
+#[cfg(feature = "wasm")]
+#[derive(Debug, Clone, Copy)]
+pub struct GlooTimer;
+
+#[cfg(feature = "wasm")]
+impl Timer for GlooTimer {
+    type Delay = gloo_timers::future::TimeoutFuture;
+
+    fn delay_until(deadline: Instant) -> Self::Delay {
+        Self::delay_for(deadline.saturating_duration_since(Instant::now()))
+    }
+
+    fn delay_for(duration: Duration) -> Self::Delay {
+        let millis = duration.as_nanos().div_ceil(1_000_000);
+        gloo_timers::future::TimeoutFuture::new(millis.min(u32::MAX.into()) as u32)
+    }
+}
+
+/// Timer of `futures-timer`, which doesn't depend on a runtime, waking the delays from the
+/// helper thread of the crate. Requires the `futures-timer` feature.
+/// ```rust
+/// use std::time::Duration;
+///
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, FuturesTimer, TimedOut};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Writer);
+///
+/// let block = link_futures!(Block, BlockFutureIdentifier, timer = FuturesTimer;
+///     #[within(Duration::from_millis(10))]
+///     Reader => pending::<()>(),
+///     Writer => pending()
+/// );
+/// let (completed_future_identifier, result) = block_on(block);
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Reader);
+/// assert_eq!(result, Err(TimedOut));
+/// ```
+#[cfg(feature = "futures-timer")]
+#[derive(Debug, Clone, Copy)]
+pub struct FuturesTimer;
+
+#[cfg(feature = "futures-timer")]
+impl Timer for FuturesTimer {
+    type Delay = futures_timer::Delay;
+
+    fn delay_until(deadline: Instant) -> Self::Delay {
+        Self::delay_for(deadline.saturating_duration_since(Instant::now()))
+    }
+
+    fn delay_for(duration: Duration) -> Self::Delay {
+        futures_timer::Delay::new(duration)
+    }
+}
+
+/// Timer used by the time-based features of the linking macros and
+/// [`LinkedBlockExt`](trait.LinkedBlockExt.html)
+#[cfg(feature = "tokio")]
+pub type DefaultTimer = TokioTimer;
+
+/// Timer waking the delays from a single thread shared by all of them, which doesn't depend on
+/// a runtime
+///
+/// The thread is spawned once a delay is polled for the first time, and keeps running until
+/// the process exits, so the timer suits the blocks driven by
+/// [`run_linked`](fn.run_linked.html) or by other executors without a timer.
+/// ```rust
+/// use std::time::Duration;
+///
+/// use futures::executor::block_on;
+/// use futures::future::pending;
+///
+/// use linked_futures::{link_futures, linked_block, ThreadTimer, TimedOut};
+///
+/// linked_block!(Block, BlockFutureIdentifier; Reader, Writer);
+///
+/// let block = link_futures!(Block, BlockFutureIdentifier, timer = ThreadTimer;
+///     #[within(Duration::from_millis(10))]
+///     Reader => pending::<()>(),
+///     Writer => pending()
+/// );
+/// let (completed_future_identifier, result) = block_on(block);
+/// assert_eq!(completed_future_identifier, BlockFutureIdentifier::Reader);
+/// assert_eq!(result, Err(TimedOut));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ThreadTimer;
+
+impl Timer for ThreadTimer {
+    type Delay = ThreadDelay;
+
+    fn delay_until(deadline: Instant) -> Self::Delay {
+        ThreadDelay {
+            deadline,
+            state: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct DelayState {
+    is_elapsed: bool,
+    waker: Option<Waker>,
+}
+
+impl DelayState {
+    fn elapse(&mut self) {
+        self.is_elapsed = true;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Delay scheduled on the thread of [`ThreadTimer`](struct.ThreadTimer.html), which is
+/// forgotten once the delay is dropped
+struct Scheduled {
+    deadline: Instant,
+    state: Weak<Mutex<DelayState>>,
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.deadline.cmp(&other.deadline)
+    }
+}
+
+static SCHEDULED: Mutex<BinaryHeap<Reverse<Scheduled>>> = Mutex::new(BinaryHeap::new());
+static RESCHEDULED: Condvar = Condvar::new();
+static SPAWN_THREAD: Once = Once::new();
+
+fn schedule(deadline: Instant, state: Weak<Mutex<DelayState>>) {
+    SPAWN_THREAD.call_once(|| {
+        thread::Builder::new()
+            .name("linked-futures-timer".into())
+            .spawn(run_timer)
+            .expect("failed to spawn the thread of ThreadTimer");
+    });
+    SCHEDULED
+        .lock()
+        .unwrap()
+        .push(Reverse(Scheduled { deadline, state }));
+    RESCHEDULED.notify_one();
+}
+
+fn run_timer() {
+    let mut scheduled = SCHEDULED.lock().unwrap();
+    loop {
+        let now = Instant::now();
+        let mut elapsed = Vec::new();
+        while matches!(scheduled.peek(), Some(Reverse(delay)) if delay.deadline <= now) {
+            elapsed.extend(
+                scheduled
+                    .pop()
+                    .and_then(|Reverse(delay)| delay.state.upgrade()),
+            );
+        }
+        if !elapsed.is_empty() {
+            // The wakers are woken without holding the lock of the scheduled delays
+            drop(scheduled);
+            for state in elapsed {
+                state.lock().unwrap().elapse();
+            }
+            scheduled = SCHEDULED.lock().unwrap();
+            continue;
+        }
+        scheduled = match scheduled.peek() {
+            Some(Reverse(delay)) => {
+                let timeout = delay.deadline.saturating_duration_since(now);
+                RESCHEDULED.wait_timeout(scheduled, timeout).unwrap().0
+            }
+            None => RESCHEDULED.wait(scheduled).unwrap(),
+        };
+    }
+}
+
+/// Future returned by [`ThreadTimer`](struct.ThreadTimer.html)
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ThreadDelay {
+    deadline: Instant,
+    state: Option<Arc<Mutex<DelayState>>>,
+}
+
+impl Future for ThreadDelay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        let deadline = self.deadline;
+        let state = self.state.get_or_insert_with(|| {
+            let state = Arc::new(Mutex::new(DelayState::default()));
+            schedule(deadline, Arc::downgrade(&state));
+            state
+        });
+        let mut state = state.lock().unwrap();
+        if state.is_elapsed {
+            return Poll::Ready(());
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
@@ -0,0 +1,55 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use tracing::Span;
+
+/// Linked future instrumented with the span of its variant, which reports when the future is
+/// started, completed, or cancelled by dropping it before completion
+#[derive(Debug)]
+pub(crate) struct Traced<F> {
+    future: F,
+    span: Span,
+    is_started: bool,
+    is_completed: bool,
+}
+
+impl<F> Traced<F> {
+    pub(crate) fn new(block: &'static str, variant: &'static str, future: F) -> Self {
+        Traced {
+            future,
+            span: tracing::info_span!("linked_future", block, variant),
+            is_started: false,
+            is_completed: false,
+        }
+    }
+}
+
+impl<F: Future> Future for Traced<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The future is structurally pinned, and never moved out
+        let this = unsafe { self.get_unchecked_mut() };
+        let _entered = this.span.enter();
+        if !this.is_started {
+            this.is_started = true;
+            tracing::info!("linked future started");
+        }
+        let output = unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx);
+        if output.is_ready() {
+            this.is_completed = true;
+            tracing::info!("linked future completed");
+        }
+        output
+    }
+}
+
+impl<F> Drop for Traced<F> {
+    fn drop(&mut self) {
+        if self.is_started && !self.is_completed {
+            let _entered = self.span.enter();
+            tracing::info!("linked future cancelled");
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use core::fmt::Debug;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+
+/// Future returned by [`link_try_futures`](macro.link_try_futures.html), which resolves with
+/// the first error, or once all of the futures complete successfully
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LinkedTryBlock<F> {
+    futures: FuturesUnordered<F>,
+    is_terminated: bool,
+}
+
+impl<F, I, E> LinkedTryBlock<F>
+where
+    F: Future<Output = Result<I, (I, E)>>,
+{
+    /// Link the futures into a block
+    pub fn new(futures: FuturesUnordered<F>) -> Self {
+        LinkedTryBlock {
+            futures,
+            is_terminated: false,
+        }
+    }
+}
+
+impl<F> LinkedTryBlock<F> {
+    /// Consume the block, returning the futures which are not yet completed
+    pub fn into_inner(self) -> FuturesUnordered<F> {
+        self.futures
+    }
+}
+
+impl<F, I, E> Future for LinkedTryBlock<F>
+where
+    F: Future<Output = Result<I, (I, E)>>,
+    I: Debug,
+{
+    type Output = Result<(), (I, E)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        assert!(
+            !self.is_terminated,
+            "LinkedTryBlock polled after completion"
+        );
+
+        loop {
+            match self.futures.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(_identifier))) => {
+                    #[cfg(feature = "log")]
+                    log::debug!("linked future {:?} completed", _identifier);
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    self.is_terminated = true;
+                    return Poll::Ready(Err(err));
+                }
+                Poll::Ready(None) => {
+                    self.is_terminated = true;
+                    return Poll::Ready(Ok(()));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<F, I, E> FusedFuture for LinkedTryBlock<F>
+where
+    F: Future<Output = Result<I, (I, E)>>,
+    I: Debug,
+{
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
@@ -0,0 +1,90 @@
+use core::future::Future;
+use core::ops::ControlFlow;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures::future::FusedFuture;
+
+use crate::LinkedBlock;
+
+/// Future returned by [`LinkedBlock::until`](struct.LinkedBlock.html#method.until), which
+/// terminates when the predicate breaks
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Until<F, P> {
+    block: LinkedBlock<F>,
+    predicate: P,
+}
+
+impl<F, I, T> LinkedBlock<F>
+where
+    F: Future<Output = (I, T)>,
+{
+    /// Evaluate `predicate` on every completed future, terminating the block once it returns
+    /// `ControlFlow::Break`. The outputs for which it continues are dropped.
+    ///
+    /// Resolves with `None` if all of the futures complete without breaking.
+    ///
+    /// ```rust
+    /// use std::ops::ControlFlow;
+    ///
+    /// use futures::executor::block_on;
+    /// use futures::future::pending;
+    ///
+    /// use linked_futures::{link_futures, linked_block};
+    ///
+    /// linked_block!(Checks, CheckFutureIdentifier; Disk, Network, Timer);
+    ///
+    /// let block = link_futures!(Checks, CheckFutureIdentifier;
+    ///     Disk => async { Ok(()) },
+    ///     Network => async { Err("unreachable") },
+    ///     Timer => pending()
+    /// );
+    /// let failed = block_on(block.until(|_, result| match result {
+    ///     Ok(()) => ControlFlow::Continue(()),
+    ///     Err(_) => ControlFlow::Break(()),
+    /// }));
+    /// assert_eq!(failed, Some((CheckFutureIdentifier::Network, Err("unreachable"))));
+    /// ```
+    pub fn until<P>(self, predicate: P) -> Until<F, P>
+    where
+        P: FnMut(&I, &T) -> ControlFlow<()>,
+    {
+        Until {
+            block: self,
+            predicate,
+        }
+    }
+}
+
+// The linked futures are pinned by `FuturesUnordered` itself, and the predicate is never pinned
+impl<F, P> Unpin for Until<F, P> {}
+
+impl<F, I, T, P> Future for Until<F, P>
+where
+    F: Future<Output = (I, T)>,
+    P: FnMut(&I, &T) -> ControlFlow<()>,
+{
+    type Output = Option<(I, T)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Until { block, predicate } = &mut *self;
+
+        block.poll_until(cx, |identifier, output| {
+            match predicate(&identifier, &output) {
+                ControlFlow::Break(()) => Some((identifier, output)),
+                ControlFlow::Continue(()) => None,
+            }
+        })
+    }
+}
+
+impl<F, I, T, P> FusedFuture for Until<F, P>
+where
+    F: Future<Output = (I, T)>,
+    P: FnMut(&I, &T) -> ControlFlow<()>,
+{
+    fn is_terminated(&self) -> bool {
+        self.block.is_terminated()
+    }
+}
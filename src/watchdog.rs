@@ -0,0 +1,312 @@
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+
+use crate::{Spawner, Timer};
+
+type Monitor<I> = Box<dyn FnOnce(Weak<Mutex<Inner<I>>>) -> BoxFuture<'static, ()> + Send>;
+
+/// Tracker of the last time the watched futures were polled, which reports the ones
+/// which haven't been polled for too long
+///
+/// Futures are watched with [`watch`](#method.watch), or by marking them with
+/// `#[watch(watchdog)]` in the linking macros. The watchdog itself is usually linked into
+/// the same block, either with [`stalled`](#method.stalled) to terminate the block with
+/// the identifier of the stalled future, or with [`monitor`](#method.monitor) to only
+/// report it:
+/// ```rust
+/// use std::time::{Duration, Instant};
+///
+/// use futures::executor::block_on;
+/// use futures::future::{pending, ready, Ready};
+///
+/// use linked_futures::{link_typed_futures, linked_block, Timer, Watchdog};
+///
+/// # struct ImmediateTimer;
+/// # impl Timer for ImmediateTimer {
+/// #     type Delay = Ready<()>;
+/// #     fn delay_until(_deadline: Instant) -> Self::Delay {
+/// #         ready(())
+/// #     }
+/// # }
+/// linked_block!(Service, ServiceFutureIdentifier, ServiceOutput; Consumer, Watchdog);
+///
+/// let watchdog = Watchdog::new();
+/// let block = link_typed_futures!(Service, ServiceFutureIdentifier, ServiceOutput;
+///     #[watch(watchdog)]
+///     Consumer => pending::<()>(),
+///     Watchdog => watchdog.stalled::<ImmediateTimer>(Duration::from_millis(0))
+/// );
+/// match block_on(block) {
+///     ServiceOutput::Watchdog(stalled) => assert_eq!(stalled, ServiceFutureIdentifier::Consumer),
+///     ServiceOutput::Consumer(()) => unreachable!(),
+/// }
+/// ```
+///
+/// The watchdog created with [`monitored`](#method.monitored) runs its monitor as a separate
+/// task instead, spawned by [`spawn_monitor`](#method.spawn_monitor), or by the first future
+/// marked with `#[watch(watchdog)]` in a linking macro given a spawner with `spawner = S`:
+/// ```rust
+/// use core::future::Future;
+/// use std::time::{Duration, Instant};
+///
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use futures::future::{pending, ready, Ready};
+/// use futures::StreamExt;
+///
+/// use linked_futures::{link_futures, linked_block, Spawner, Timer, Watchdog};
+///
+/// # struct ImmediateTimer;
+/// # impl Timer for ImmediateTimer {
+/// #     type Delay = Ready<()>;
+/// #     fn delay_until(_deadline: Instant) -> Self::Delay {
+/// #         ready(())
+/// #     }
+/// # }
+/// struct ThreadSpawner;
+///
+/// impl Spawner for ThreadSpawner {
+///     fn spawn<F>(task: F)
+///     where
+///         F: Future<Output = ()> + Send + 'static,
+///     {
+///         std::thread::spawn(move || block_on(task));
+///     }
+/// }
+///
+/// linked_block!(Service, ServiceFutureIdentifier; Consumer, Stop);
+///
+/// let (stalled, mut reports) = mpsc::unbounded();
+/// let threshold = Duration::from_millis(0);
+/// let watchdog = Watchdog::monitored::<ImmediateTimer>(threshold, move |identifier, _| {
+///     let _ = stalled.unbounded_send(identifier);
+/// });
+/// let block = link_futures!(Service, ServiceFutureIdentifier, spawner = ThreadSpawner;
+///     #[watch(watchdog)]
+///     Consumer => pending(),
+///     Stop => async move { reports.next().await.unwrap() }
+/// );
+/// let (_, stalled) = block_on(block);
+/// assert_eq!(stalled, ServiceFutureIdentifier::Consumer);
+/// ```
+#[derive(Debug)]
+pub struct Watchdog<I> {
+    inner: Arc<Mutex<Inner<I>>>,
+}
+
+struct Inner<I> {
+    next_token: usize,
+    watched: Vec<Watch<I>>,
+    monitor: Option<Monitor<I>>,
+}
+
+impl<I: fmt::Debug> fmt::Debug for Inner<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Inner")
+            .field("next_token", &self.next_token)
+            .field("watched", &self.watched)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+struct Watch<I> {
+    token: usize,
+    identifier: I,
+    polled_at: Instant,
+    is_reported: bool,
+}
+
+impl<I> Clone for Watchdog<I> {
+    fn clone(&self) -> Self {
+        Watchdog {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<I> Default for Watchdog<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I> Watchdog<I> {
+    /// Create a watchdog without watched futures
+    pub fn new() -> Self {
+        Watchdog {
+            inner: Arc::new(Mutex::new(Inner {
+                next_token: 0,
+                watched: Vec::new(),
+                monitor: None,
+            })),
+        }
+    }
+
+    /// Watch the polls of `future`, reporting it with `identifier`. The future is no longer
+    /// watched once it completes or is dropped.
+    pub fn watch<F: Future>(&self, identifier: I, future: F) -> Watched<F, I> {
+        let mut inner = self.inner.lock().unwrap();
+        let token = inner.next_token;
+        inner.next_token += 1;
+        inner.watched.push(Watch {
+            token,
+            identifier,
+            polled_at: Instant::now(),
+            is_reported: false,
+        });
+
+        Watched {
+            future,
+            watchdog: self.clone(),
+            token,
+        }
+    }
+
+    /// Watch the polls of `future` as [`watch`](#method.watch) does, spawning the monitor of
+    /// the watchdog with the spawner `S` if it's not spawned yet
+    pub fn watch_on<S: Spawner, F: Future>(&self, identifier: I, future: F) -> Watched<F, I> {
+        self.spawn_monitor::<S>();
+        self.watch(identifier, future)
+    }
+
+    /// Spawn the monitor of the watchdog created with [`monitored`](#method.monitored) with
+    /// the spawner `S`, unless it's spawned already. The monitor stops once the watchdog and
+    /// all of its watched futures are dropped.
+    pub fn spawn_monitor<S: Spawner>(&self) {
+        let monitor = self.inner.lock().unwrap().monitor.take();
+        if let Some(monitor) = monitor {
+            S::spawn(monitor(Arc::downgrade(&self.inner)));
+        }
+    }
+
+    fn polled(&self, token: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(watch) = inner.watched.iter_mut().find(|watch| watch.token == token) {
+            watch.polled_at = Instant::now();
+            watch.is_reported = false;
+        }
+    }
+
+    fn unwatch(&self, token: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.watched.retain(|watch| watch.token != token);
+    }
+}
+
+impl<I: Clone + Send + 'static> Watchdog<I> {
+    /// Create a watchdog without watched futures, whose monitor calls `on_stalled` as
+    /// [`monitor`](#method.monitor) does, checking with the timer `T`, once it's spawned
+    pub fn monitored<T>(
+        threshold: Duration,
+        mut on_stalled: impl FnMut(I, Duration) + Send + 'static,
+    ) -> Self
+    where
+        T: Timer,
+        T::Delay: Send + 'static,
+    {
+        let watchdog = Self::new();
+        let monitor: Monitor<I> = Box::new(move |inner| {
+            Box::pin(async move {
+                // The watchdog is only upgraded for the checks, so the monitor doesn't keep
+                // it alive
+                while let Some(inner) = inner.upgrade() {
+                    let checked = Watchdog { inner }.check(threshold);
+                    match checked {
+                        Ok((identifier, idle)) => on_stalled(identifier, idle),
+                        Err(deadline) => T::delay_until(deadline).await,
+                    }
+                }
+            })
+        });
+        watchdog.inner.lock().unwrap().monitor = Some(monitor);
+        watchdog
+    }
+}
+
+impl<I: Clone> Watchdog<I> {
+    /// Identifiers of the watched futures, with the time since they were last polled
+    pub fn idle(&self) -> Vec<(I, Duration)> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .watched
+            .iter()
+            .map(|watch| (watch.identifier.clone(), watch.polled_at.elapsed()))
+            .collect()
+    }
+
+    /// Resolve with the identifier of the first watched future which isn't polled for
+    /// `threshold`, checking with the timer `T`
+    pub async fn stalled<T: Timer>(&self, threshold: Duration) -> I {
+        loop {
+            match self.check(threshold) {
+                Ok((identifier, _)) => return identifier,
+                Err(deadline) => T::delay_until(deadline).await,
+            }
+        }
+    }
+
+    /// Call `on_stalled` with the identifier of every watched future which isn't polled for
+    /// `threshold`, and the time since its last poll. The future is reported again only
+    /// after it's polled. Never completes.
+    pub async fn monitor<T: Timer>(
+        &self,
+        threshold: Duration,
+        mut on_stalled: impl FnMut(I, Duration),
+    ) {
+        loop {
+            match self.check(threshold) {
+                Ok((identifier, idle)) => on_stalled(identifier, idle),
+                Err(deadline) => T::delay_until(deadline).await,
+            }
+        }
+    }
+
+    /// Find a stalled future which isn't reported yet, or the time of the next check
+    fn check(&self, threshold: Duration) -> Result<(I, Duration), Instant> {
+        let mut inner = self.inner.lock().unwrap();
+        let now = Instant::now();
+        let mut deadline = now + threshold;
+        for watch in inner.watched.iter_mut().filter(|watch| !watch.is_reported) {
+            let stalled_at = watch.polled_at + threshold;
+            if stalled_at <= now {
+                watch.is_reported = true;
+                return Ok((watch.identifier.clone(), now - watch.polled_at));
+            }
+            deadline = deadline.min(stalled_at);
+        }
+        Err(deadline)
+    }
+}
+
+/// Future returned by [`Watchdog::watch`](struct.Watchdog.html#method.watch)
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Watched<F, I> {
+    future: F,
+    watchdog: Watchdog<I>,
+    token: usize,
+}
+
+impl<F: Future, I> Future for Watched<F, I> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The future is structurally pinned, and never moved out
+        let this = unsafe { self.get_unchecked_mut() };
+        this.watchdog.polled(this.token);
+        unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx)
+    }
+}
+
+impl<F, I> Drop for Watched<F, I> {
+    fn drop(&mut self) {
+        self.watchdog.unwatch(self.token);
+    }
+}
@@ -1,13 +1,24 @@
 #[cfg(test)]
 mod tests {
-    use linked_futures::{link_futures, linked_block};
+    use linked_futures::{
+        expect_variant, join_futures, link_biased_futures, link_futures, link_local_futures,
+        link_parts, link_select_ok_futures, link_spawned_futures, link_try_futures,
+        link_typed_futures, link_unit_futures, linked, linked_block, match_completed,
+    };
+
+    use linked_futures::{
+        AbortHandles, Escalated, LinkedBlock, LinkedBlockBuilder, LinkedFutures, LinkedTaskBlock,
+        LocalSpawner, PanicPolicy, Panicked, Spawner,
+    };
 
     use futures::channel::oneshot;
     use futures::executor::block_on;
+    use futures::FutureExt;
 
     linked_block!(SimpleBlock, SimpleBlockFutureIdentifier; Never, Stop);
 
     #[test]
+    #[allow(clippy::redundant_async_block)]
     fn it_works() {
         let (_tx, rx) = oneshot::channel::<()>();
         let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
@@ -19,4 +30,1134 @@ mod tests {
         let (stopped_future_name, _) = block_on(async { block.await });
         assert_eq!(stopped_future_name, SimpleBlockFutureIdentifier::Stop);
     }
+
+    #[test]
+    fn linked_block_is_future() {
+        let (_tx, rx) = oneshot::channel::<()>();
+        let block: LinkedBlock<_> = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => async {
+                let _ = rx.await;
+            },
+            Stop => async { }
+        );
+        let (stopped_future_name, _) = block_on(block);
+        assert_eq!(stopped_future_name, SimpleBlockFutureIdentifier::Stop);
+    }
+
+    #[test]
+    fn identifier_names() {
+        assert_eq!(SimpleBlockFutureIdentifier::Stop.to_string(), "Stop");
+        assert_eq!(SimpleBlockFutureIdentifier::Never.as_str(), "Never");
+        assert_eq!(
+            "Never".parse::<SimpleBlockFutureIdentifier>(),
+            Ok(SimpleBlockFutureIdentifier::Never)
+        );
+        let err = "Reader".parse::<SimpleBlockFutureIdentifier>().unwrap_err();
+        assert_eq!(err.to_string(), "unknown linked future `Reader`");
+    }
+
+    #[test]
+    fn identifier_serde() {
+        use serde::de::value::{Error, StrDeserializer};
+        use serde::de::IntoDeserializer;
+        use serde::Deserialize;
+
+        let deserializer: StrDeserializer<'_, Error> = "Stop".into_deserializer();
+        assert_eq!(
+            SimpleBlockFutureIdentifier::deserialize(deserializer),
+            Ok(SimpleBlockFutureIdentifier::Stop)
+        );
+        let deserializer: StrDeserializer<'_, Error> = "Reader".into_deserializer();
+        assert!(SimpleBlockFutureIdentifier::deserialize(deserializer).is_err());
+    }
+
+    #[test]
+    fn skipped_variant() {
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => skip,
+            Stop => async { }
+        );
+        let (stopped_future_name, _) = block_on(block);
+        assert_eq!(stopped_future_name, SimpleBlockFutureIdentifier::Stop);
+    }
+
+    #[test]
+    fn expected_variant() {
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => futures::future::pending(),
+            Stop => async { 1 }
+        );
+        assert_eq!(
+            expect_variant!(block_on(block), SimpleBlockFutureIdentifier::Stop),
+            1
+        );
+
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => async { },
+            Stop => futures::future::pending()
+        );
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            expect_variant!(block_on(block), SimpleBlockFutureIdentifier::Stop);
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn optional_variant() {
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => optional None::<futures::future::Ready<()>>,
+            Stop => optional Some(async { })
+        );
+        expect_variant!(block_on(block), SimpleBlockFutureIdentifier::Stop);
+    }
+
+    #[test]
+    fn guarded_variant() {
+        let is_enabled = false;
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never if is_enabled => async { },
+            #[cfg(all())]
+            Stop if !is_enabled => async { }
+        );
+        expect_variant!(block_on(block), SimpleBlockFutureIdentifier::Stop);
+    }
+
+    #[test]
+    fn all_variants_guarded() {
+        let is_enabled = false;
+        let mut block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never if is_enabled => async { },
+            Stop if is_enabled => async { }
+        );
+        block_on(async {
+            assert!(futures::poll!(&mut block).is_pending());
+            assert!(futures::poll!(&mut block).is_pending());
+        });
+    }
+
+    #[test]
+    fn empty_blocks_pend() {
+        let empty = || {
+            link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+                Never if false => async { },
+                Stop if false => async { }
+            )
+        };
+        let mut all_ready = empty().all_ready();
+        let mut remaining = empty().into_remaining();
+        let mut drain = empty().drain(futures::future::pending);
+        let mut linked = LinkedFutures::new();
+        block_on(async {
+            assert!(futures::poll!(&mut all_ready).is_pending());
+            assert!(futures::poll!(&mut remaining).is_pending());
+            assert!(futures::poll!(&mut drain).is_pending());
+            assert!(futures::poll!(&mut linked).is_pending());
+            linked.push("stop", async { "stopped" });
+            assert_eq!(
+                futures::poll!(&mut linked),
+                std::task::Poll::Ready(("stop", "stopped"))
+            );
+        });
+    }
+
+    #[test]
+    fn drop_order_of_link_list() {
+        use std::sync::{Arc, Mutex};
+
+        use linked_futures::DropOrder;
+
+        struct Dropped(&'static str, Arc<Mutex<Vec<&'static str>>>);
+
+        impl Drop for Dropped {
+            fn drop(&mut self) {
+                self.1.lock().unwrap().push(self.0);
+            }
+        }
+
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let stop = Dropped("stop", dropped.clone());
+        let never = Dropped("never", dropped.clone());
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Stop => async move {
+                let _stop = stop;
+                futures::future::pending::<()>().await
+            },
+            Never => async move {
+                let _never = never;
+                futures::future::pending::<()>().await
+            }
+        );
+        let mut block = block.drop_order(DropOrder::Declaration);
+        block_on(async {
+            assert!(futures::poll!(&mut block).is_pending());
+        });
+        drop(block);
+        assert_eq!(*dropped.lock().unwrap(), ["stop", "never"]);
+    }
+
+    #[test]
+    fn cancel_hooks_of_block() {
+        use std::sync::{Arc, Mutex};
+
+        use linked_futures::{CancelHooks, LinkedError};
+
+        let hooks = CancelHooks::fallible();
+        let flushed = Arc::new(Mutex::new(Vec::new()));
+        let (first, second) = (flushed.clone(), flushed.clone());
+        let mut running = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            #[on_cancel(hooks, move || async move { first.lock().unwrap().push("running") })]
+            Never => futures::future::pending::<()>(),
+            Stop => futures::future::pending()
+        )
+        .on_cancel(&hooks, futures::future::pending);
+        let stopped = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            #[on_cancel(hooks, move || async move {
+                second.lock().unwrap().push("stopped");
+                Err("not flushed")
+            })]
+            Never => futures::future::pending(),
+            Stop => async {}
+        )
+        .on_cancel(&hooks, futures::future::pending);
+
+        block_on(async {
+            assert!(futures::poll!(&mut running).is_pending());
+        });
+        let report = block_on(stopped);
+        assert_eq!(*flushed.lock().unwrap(), ["stopped"]);
+        assert_eq!(
+            report.cleanup_errors,
+            [(SimpleBlockFutureIdentifier::Never, "not flushed")]
+        );
+        assert!(matches!(
+            report.into_result(),
+            Err(LinkedError::Cleanup(
+                SimpleBlockFutureIdentifier::Never,
+                "not flushed"
+            ))
+        ));
+    }
+
+    linked_block!(PartedBlock, PartedBlockFutureIdentifier, parts = PartedBlockParts; Never, Stop);
+
+    #[test]
+    fn taken_part() {
+        let mut parts = link_parts!(PartedBlockParts, PartedBlockFutureIdentifier;
+            Never => async { },
+            Stop => futures::future::pending()
+        );
+        let never = parts.Never.take().unwrap();
+        assert!(parts.Never.is_none());
+        let mut block = parts.link();
+        block_on(async {
+            assert!(futures::poll!(&mut block).is_pending());
+        });
+        assert_eq!(block_on(never), ());
+    }
+
+    #[test]
+    fn taken_part_keeps_type() {
+        let mut parts = link_parts!(PartedBlockParts, PartedBlockFutureIdentifier;
+            Never => futures::future::pending::<u32>(),
+            Stop => futures::future::ready(42)
+        );
+        let stop: futures::future::Ready<u32> = parts.Stop.take().unwrap();
+        assert_eq!(block_on(stop), 42);
+        assert_eq!(parts.len(), 1);
+    }
+
+    #[test]
+    fn identified_error() {
+        use linked_futures::IdentifiedError;
+
+        let block = link_try_futures!(SimpleBlock, SimpleBlockFutureIdentifier,
+            identified_error = IdentifiedError<SimpleBlockFutureIdentifier, &str>;
+            Never => futures::future::pending::<Result<(), &str>>(),
+            Stop => async { Err::<(), _>("stopped") }
+        );
+        assert_eq!(
+            block_on(block),
+            Err((
+                SimpleBlockFutureIdentifier::Stop,
+                IdentifiedError(SimpleBlockFutureIdentifier::Stop, "stopped")
+            ))
+        );
+    }
+
+    #[test]
+    fn restarted_future() {
+        let mut runs = 0;
+        let block = link_try_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            #[restart]
+            Never => || {
+                runs += 1;
+                async { Ok::<_, &str>(()) }
+            },
+            Stop => async { Err::<(), _>("stopped") }
+        );
+        assert_eq!(
+            block_on(block),
+            Err((SimpleBlockFutureIdentifier::Stop, "stopped"))
+        );
+        assert!(runs >= 1);
+    }
+
+    #[test]
+    fn escalated_future() {
+        let mut runs = 0;
+        let block = link_try_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            #[restart(max = 2, within = std::time::Duration::from_secs(60))]
+            Never => || {
+                runs += 1;
+                async {}
+            },
+            Stop => futures::future::pending::<Result<(), _>>()
+        );
+        assert_eq!(
+            block_on(block),
+            Err((
+                SimpleBlockFutureIdentifier::Never,
+                Escalated(SimpleBlockFutureIdentifier::Never)
+            ))
+        );
+        assert_eq!(runs, 3);
+    }
+
+    #[test]
+    fn fatal_error() {
+        let mut runs = 0;
+        let block = link_try_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            #[restart(fatal = |err| *err == "fatal")]
+            Never => || {
+                runs += 1;
+                let err = if runs < 3 { "recoverable" } else { "fatal" };
+                async move { Err::<(), _>(err) }
+            },
+            Stop => futures::future::pending::<Result<(), _>>()
+        );
+        assert_eq!(
+            block_on(block),
+            Err((SimpleBlockFutureIdentifier::Never, "fatal"))
+        );
+        assert_eq!(runs, 3);
+    }
+
+    #[test]
+    fn detached_future() {
+        let block = link_unit_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            #[detached]
+            Never => async { },
+            Stop => async { }
+        );
+        assert_eq!(block_on(block), SimpleBlockFutureIdentifier::Stop);
+    }
+
+    #[test]
+    fn aborted_future() {
+        let handles = AbortHandles::new();
+        assert!(!handles.abort(&SimpleBlockFutureIdentifier::Never));
+
+        let (tx, rx) = oneshot::channel::<()>();
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            #[abortable(handles)]
+            Never => async { let _ = rx.await; },
+            Stop => async { }
+        );
+        expect_variant!(block_on(block), SimpleBlockFutureIdentifier::Stop);
+        assert!(!handles.contains(&SimpleBlockFutureIdentifier::Never));
+        drop(tx);
+    }
+
+    #[test]
+    fn panicked_future() {
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => futures::future::pending(),
+            #[catch_unwind]
+            Stop => async { panic!("stopped") }
+        );
+        match block_on(block.catch_unwind()) {
+            Err(Panicked(id, _)) => assert_eq!(id, SimpleBlockFutureIdentifier::Stop),
+            Ok(_) => panic!("panic is not caught"),
+        }
+
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => futures::future::pending(),
+            #[on_panic(PanicPolicy::Complete)]
+            Stop => || async { panic!("stopped") }
+        );
+        match block_on(block.catch_unwind()) {
+            Err(Panicked(id, _)) => assert_eq!(id, SimpleBlockFutureIdentifier::Stop),
+            Ok(_) => panic!("panic is not caught"),
+        }
+
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            #[catch_unwind]
+            Never => futures::future::pending(),
+            Stop => async { panic!("stopped") }
+        );
+        let block = std::panic::AssertUnwindSafe(block.catch_unwind());
+        let result = std::panic::catch_unwind(|| block_on(block));
+        assert_eq!(*result.unwrap_err().downcast::<&str>().unwrap(), "stopped");
+    }
+
+    #[test]
+    fn dynamic_futures() {
+        let mut block = LinkedFutures::new();
+        let (tx, rx) = oneshot::channel();
+        block.push("never", futures::future::pending().boxed());
+        block.push("stop", async { rx.await.unwrap() }.boxed());
+        tx.send(1).unwrap();
+        assert_eq!(block_on(block), ("stop", 1));
+    }
+
+    #[test]
+    fn replaced_at_capacity() {
+        let mut block = LinkedFutures::with_capacity(1);
+        block.push("worker", futures::future::pending().boxed());
+        assert!(matches!(
+            block.replace("worker", async { 1 }.boxed()),
+            Ok(true)
+        ));
+        let key = match block.replace("listener", async { 2 }.boxed()) {
+            Err((key, _)) => key,
+            Ok(_) => unreachable!(),
+        };
+        assert_eq!(key, "listener");
+        assert_eq!(block_on(block), ("worker", 1));
+    }
+
+    #[test]
+    fn linked_streams_in_order() {
+        use futures::stream::{self, StreamExt};
+        use linked_futures::{LinkedStreams, StreamEvent};
+
+        let mut streams = LinkedStreams::with_capacity(2);
+        streams.push("orders", stream::iter(vec![1, 2, 3]).boxed());
+        let handle = streams.handle();
+        assert!(handle
+            .push("refunds", stream::iter(vec![4]).boxed())
+            .is_ok());
+        assert!(handle.push("audits", stream::empty().boxed()).is_err());
+        drop(handle);
+
+        let events = block_on(streams.take(6).collect::<Vec<_>>());
+        let orders = events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::Item("orders", item) => Some(*item),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(orders, [1, 2, 3]);
+        assert!(events.contains(&StreamEvent::Item("refunds", 4)));
+        assert!(events.contains(&StreamEvent::Ended("refunds")));
+        assert!(events.contains(&StreamEvent::Ended("orders")));
+    }
+
+    #[test]
+    fn built_join_block() {
+        let mut builder = LinkedBlockBuilder::new();
+        for shard in 0..3 {
+            builder = builder.add(shard, async move { shard * 10 });
+        }
+        let outputs = block_on(builder.join());
+        assert_eq!(
+            outputs.into_iter().collect::<Vec<_>>(),
+            [(0, 0), (1, 10), (2, 20)]
+        );
+    }
+
+    #[test]
+    fn cancelled_dynamic_future() {
+        let mut block = LinkedFutures::new();
+        let (tx, rx) = oneshot::channel();
+        block.push("cancelled", async { 0 }.boxed());
+        block.push("stop", async { rx.await.unwrap() }.boxed());
+        assert!(block.cancel(&"cancelled"));
+        block_on(async {
+            assert!(futures::poll!(&mut block).is_pending());
+            assert_eq!(block.len(), 1);
+            tx.send(1).unwrap();
+            assert_eq!((&mut block).await, ("stop", 1));
+        });
+    }
+
+    #[test]
+    fn cfg_variants() {
+        linked_block!(CfgBlock, CfgBlockFutureIdentifier;
+            Never,
+            #[cfg(any())]
+            Disabled,
+            #[cfg(all())]
+            Stop
+        );
+
+        assert_eq!(
+            CfgBlockFutureIdentifier::ALL,
+            [
+                CfgBlockFutureIdentifier::Never,
+                CfgBlockFutureIdentifier::Stop
+            ]
+        );
+        let block = link_futures!(CfgBlock, CfgBlockFutureIdentifier;
+            Never => skip,
+            #[cfg(any())]
+            Disabled => async { },
+            #[cfg(all())]
+            Stop => async { }
+        );
+        let (stopped_future_name, _) = block_on(block);
+        assert_eq!(stopped_future_name, CfgBlockFutureIdentifier::Stop);
+    }
+
+    #[test]
+    fn unit_futures() {
+        let (_tx, rx) = oneshot::channel::<()>();
+        let block = link_unit_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => async {
+                let _ = rx.await;
+            },
+            Stop => async { }
+        );
+        assert_eq!(block_on(block), SimpleBlockFutureIdentifier::Stop);
+    }
+
+    #[test]
+    fn biased_futures() {
+        for _ in 0..10 {
+            let block = link_biased_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+                Never => async { },
+                Stop => async { }
+            );
+            expect_variant!(block_on(block), SimpleBlockFutureIdentifier::Never);
+        }
+
+        let block = link_biased_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => skip,
+            Stop => async { }
+        );
+        expect_variant!(block_on(block), SimpleBlockFutureIdentifier::Stop);
+    }
+
+    #[test]
+    fn try_futures() {
+        let block = link_try_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => skip,
+            Stop => async { Err::<(), _>("stopped") }
+        );
+        assert_eq!(
+            block_on(block),
+            Err((SimpleBlockFutureIdentifier::Stop, "stopped"))
+        );
+
+        let block = link_try_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => async { Ok::<_, ()>(1) },
+            Stop => async { Ok(()) }
+        );
+        assert_eq!(block_on(block), Ok(()));
+
+        let block = link_try_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => skip,
+            Stop => async { Ok::<_, ()>(()) }
+        );
+        assert_eq!(block_on(block), Ok(()));
+
+        let block = link_try_futures!(SimpleBlock, SimpleBlockFutureIdentifier, error = String;
+            Never => skip,
+            Stop => async { Err::<(), _>("stopped") }
+        );
+        assert_eq!(
+            block_on(block),
+            Err((SimpleBlockFutureIdentifier::Stop, "stopped".to_string()))
+        );
+    }
+
+    #[test]
+    fn select_ok_futures() {
+        let block = link_select_ok_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => skip,
+            Stop => async { Ok::<_, ()>("stopped") }
+        );
+        assert_eq!(
+            block_on(block),
+            Ok((SimpleBlockFutureIdentifier::Stop, "stopped"))
+        );
+
+        let block = link_select_ok_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => async { Err::<(), _>(1) },
+            Stop => async { Err(2) }
+        );
+        let mut errors = block_on(block).unwrap_err();
+        errors.sort();
+        assert_eq!(
+            errors,
+            [
+                (SimpleBlockFutureIdentifier::Never, 1),
+                (SimpleBlockFutureIdentifier::Stop, 2)
+            ]
+        );
+
+        let block = link_select_ok_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => skip,
+            Stop => async { Err::<(), _>("failed") }
+        );
+        assert_eq!(
+            block_on(block),
+            Err(vec![(SimpleBlockFutureIdentifier::Stop, "failed")])
+        );
+    }
+
+    #[test]
+    fn joined_futures() {
+        let block = join_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => async { 1 },
+            Stop => async { 2 }
+        );
+        let outputs = block_on(block).into_iter().collect::<Vec<_>>();
+        assert_eq!(
+            outputs,
+            [
+                (SimpleBlockFutureIdentifier::Never, 1),
+                (SimpleBlockFutureIdentifier::Stop, 2)
+            ]
+        );
+
+        let block = join_futures!(SimpleBlock, SimpleBlockFutureIdentifier, quorum = 1;
+            Never => futures::future::pending(),
+            Stop => async { 2 }
+        );
+        let outputs = block_on(block).into_iter().collect::<Vec<_>>();
+        assert_eq!(outputs, [(SimpleBlockFutureIdentifier::Stop, 2)]);
+    }
+
+    #[test]
+    fn typed_output() {
+        linked_block!(TypedBlock, TypedBlockFutureIdentifier, TypedBlockOutput; Never, Value);
+
+        let block = link_typed_futures!(TypedBlock, TypedBlockFutureIdentifier, TypedBlockOutput;
+            Never => futures::future::pending::<()>(),
+            Value => async { "value" }
+        );
+        let output = block_on(block);
+        assert_eq!(output.identifier(), TypedBlockFutureIdentifier::Value);
+        assert_eq!(output, TypedBlockOutput::Value("value"));
+        let value = match_completed!(output, TypedBlockOutput;
+            Value => |value: &str| Some(value.len()),
+            _ => None
+        );
+        assert_eq!(value, Some(5));
+    }
+
+    #[test]
+    fn linked_inline() {
+        let (_tx, rx) = oneshot::channel::<()>();
+        linked!(let block: InlineFutureIdentifier;
+            Never => async {
+                let _ = rx.await;
+            },
+            Stop => async { }
+        );
+        let (stopped_future_name, _) = block_on(block);
+        assert_eq!(stopped_future_name, InlineFutureIdentifier::Stop);
+    }
+
+    #[test]
+    fn local_futures() {
+        let state = std::rc::Rc::new(std::cell::Cell::new(0));
+        let stop_state = state.clone();
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+            Never => futures::future::pending::<()>(),
+            Stop => async move { stop_state.set(1) }
+        );
+        let (completed_future_identifier, _) = block_on(block);
+        assert_eq!(
+            completed_future_identifier,
+            SimpleBlockFutureIdentifier::Stop
+        );
+        assert_eq!(state.get(), 1);
+    }
+
+    struct ThreadSpawner;
+
+    impl Spawner for ThreadSpawner {
+        fn spawn<F>(task: F)
+        where
+            F: std::future::Future<Output = ()> + Send + 'static,
+        {
+            std::thread::spawn(move || block_on(task));
+        }
+    }
+
+    #[test]
+    fn spawned_on_custom_spawner() {
+        let (_sender, receiver) = oneshot::channel::<()>();
+        let mut block = LinkedTaskBlock::new();
+        block.spawn_on::<ThreadSpawner, _>(async move {
+            let _ = receiver.await;
+        });
+        block.spawn_on::<ThreadSpawner, _>(async { panic!("task failed") });
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| block_on(block)));
+        let panic = result.unwrap_err();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"task failed"));
+    }
+
+    #[test]
+    fn spawned_futures_on_custom_spawner() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static SPAWNED: AtomicUsize = AtomicUsize::new(0);
+        static SPAWNED_BLOCKING: AtomicUsize = AtomicUsize::new(0);
+
+        struct CountingSpawner;
+
+        impl Spawner for CountingSpawner {
+            fn spawn<F>(task: F)
+            where
+                F: std::future::Future<Output = ()> + Send + 'static,
+            {
+                SPAWNED.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || block_on(task));
+            }
+
+            fn spawn_blocking<C>(call: C)
+            where
+                C: FnOnce() + Send + 'static,
+            {
+                SPAWNED_BLOCKING.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(call);
+            }
+        }
+
+        let block = link_spawned_futures!(SimpleBlock, SimpleBlockFutureIdentifier,
+            spawner = CountingSpawner;
+            Never => futures::future::pending::<u32>(),
+            Stop => blocking || 42
+        );
+        let (completed_future_identifier, output) = block_on(block);
+        assert_eq!(
+            completed_future_identifier,
+            SimpleBlockFutureIdentifier::Stop
+        );
+        assert_eq!(output, 42);
+        assert_eq!(SPAWNED.load(Ordering::SeqCst), 2);
+        assert_eq!(SPAWNED_BLOCKING.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn cancel_hooks_on_custom_spawner() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use linked_futures::CancelHooks;
+
+        static SPAWNED: AtomicUsize = AtomicUsize::new(0);
+
+        struct CountingSpawner;
+
+        impl Spawner for CountingSpawner {
+            fn spawn<F>(task: F)
+            where
+                F: std::future::Future<Output = ()> + Send + 'static,
+            {
+                SPAWNED.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || block_on(task));
+            }
+        }
+
+        let hooks = CancelHooks::new();
+        let (flushed, flushed_on) = oneshot::channel();
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier,
+            spawner = CountingSpawner;
+            #[on_cancel(hooks, move || async move {
+                flushed.send(std::thread::current().id()).unwrap()
+            })]
+            Never => futures::future::pending::<()>(),
+            Stop => async {}
+        );
+        let report = block_on(block.on_cancel(&hooks, futures::future::pending));
+        assert_eq!(report.id, SimpleBlockFutureIdentifier::Stop);
+        assert_eq!(SPAWNED.load(Ordering::SeqCst), 1);
+        assert_ne!(block_on(flushed_on).unwrap(), std::thread::current().id());
+    }
+
+    thread_local! {
+        static LOCAL_SPAWNER: std::cell::RefCell<Option<futures::executor::LocalSpawner>> =
+            const { std::cell::RefCell::new(None) };
+    }
+
+    struct PoolSpawner;
+
+    impl LocalSpawner for PoolSpawner {
+        fn spawn_local<F>(task: F)
+        where
+            F: std::future::Future<Output = ()> + 'static,
+        {
+            use futures::task::LocalSpawnExt;
+
+            LOCAL_SPAWNER.with(|spawner| {
+                let spawner = spawner.borrow();
+                spawner.as_ref().unwrap().spawn_local(task).unwrap();
+            });
+        }
+    }
+
+    #[test]
+    fn local_futures_on_custom_spawner() {
+        let mut pool = futures::executor::LocalPool::new();
+        LOCAL_SPAWNER.with(|spawner| *spawner.borrow_mut() = Some(pool.spawner()));
+        let state = std::rc::Rc::new(std::cell::Cell::new(1));
+        let stop_state = state.clone();
+        let block = link_local_futures!(SimpleBlock, SimpleBlockFutureIdentifier,
+            spawner = PoolSpawner;
+            Never => futures::future::pending::<usize>(),
+            Stop => async move { stop_state.get() + 1 }
+        );
+        let (completed_future_identifier, output) = pool.run_until(block);
+        assert_eq!(
+            completed_future_identifier,
+            SimpleBlockFutureIdentifier::Stop
+        );
+        assert_eq!(output, 2);
+    }
+
+    #[test]
+    fn local_futures_on_local_set() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct Dropped(Rc<RefCell<Vec<&'static str>>>);
+
+        impl Drop for Dropped {
+            fn drop(&mut self) {
+                self.0.borrow_mut().push("never");
+            }
+        }
+
+        let mut runtime = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .build()
+            .unwrap();
+        let local = tokio::task::LocalSet::new();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let (never_events, stop_events) = (events.clone(), events.clone());
+        let (completed_future_identifier, output) = runtime.block_on(local.run_until(async {
+            link_local_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+                Never => async move {
+                    let _dropped = Dropped(never_events);
+                    futures::future::pending::<usize>().await
+                },
+                Stop => async move {
+                    stop_events.borrow_mut().push("stop");
+                    stop_events.borrow().len()
+                }
+            )
+            .await
+        }));
+        assert_eq!(
+            completed_future_identifier,
+            SimpleBlockFutureIdentifier::Stop
+        );
+        assert_eq!(output, 1);
+        // The aborted task is dropped once the `LocalSet` runs again
+        runtime
+            .block_on(local.run_until(async { tokio::task::spawn_local(async {}).await }))
+            .unwrap();
+        assert_eq!(*events.borrow(), ["stop", "never"]);
+    }
+
+    #[test]
+    fn thread_variant() {
+        linked_block!(LegacyBlock, LegacyBlockFutureIdentifier; Legacy, Never);
+
+        let block = link_futures!(LegacyBlock, LegacyBlockFutureIdentifier;
+            Legacy => thread |stop| stop.is_stopped(),
+            Never => futures::future::pending()
+        );
+        let (completed_future_identifier, is_stopped) = block_on(block);
+        assert_eq!(
+            completed_future_identifier,
+            LegacyBlockFutureIdentifier::Legacy
+        );
+        assert!(!is_stopped);
+
+        let block = link_futures!(LegacyBlock, LegacyBlockFutureIdentifier;
+            Legacy => thread |_stop| panic!("legacy loop failed"),
+            Never => futures::future::pending::<()>()
+        );
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| block_on(block)));
+        let panic = result.unwrap_err();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"legacy loop failed"));
+    }
+
+    #[test]
+    fn thread_detached_on_drop() {
+        use linked_futures::LinkedThread;
+
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let thread = LinkedThread::spawn(move |_stop| {
+            let _ = released.recv();
+        });
+        let mut exited = thread.exited();
+        // The call ignores the stop flag, so joining on drop would never return
+        drop(thread);
+        assert!(futures::FutureExt::now_or_never(&mut exited).is_none());
+        release.send(()).unwrap();
+        block_on(exited);
+    }
+
+    struct ImmediateTimer;
+
+    impl linked_futures::Timer for ImmediateTimer {
+        type Delay = futures::future::Ready<()>;
+
+        fn delay_until(_deadline: std::time::Instant) -> Self::Delay {
+            futures::future::ready(())
+        }
+    }
+
+    #[test]
+    fn custom_timer() {
+        let mut attempts = 0;
+        let backoff = linked_futures::Backoff::constant(std::time::Duration::from_secs(60));
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier, timer = ImmediateTimer;
+            #[retry(max = 2, backoff = backoff)]
+            Never => || {
+                attempts += 1;
+                async { Err::<(), _>("failed") }
+            },
+            Stop => futures::future::pending()
+        );
+        let (completed_future_identifier, result) = block_on(block);
+        assert_eq!(
+            completed_future_identifier,
+            SimpleBlockFutureIdentifier::Never
+        );
+        assert_eq!(result.unwrap_err().attempts, 3);
+        assert_eq!(attempts, 3);
+
+        let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier, timer = ImmediateTimer;
+            #[within(std::time::Duration::from_secs(60))]
+            Never => futures::future::pending::<()>(),
+            Stop => futures::future::pending()
+        );
+        let (completed_future_identifier, result) = block_on(block);
+        assert_eq!(
+            completed_future_identifier,
+            SimpleBlockFutureIdentifier::Never
+        );
+        assert_eq!(result, Err(linked_futures::TimedOut));
+    }
+
+    #[test]
+    fn thread_timer_delays() {
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        use linked_futures::{ThreadTimer, Timer};
+
+        let elapsed = Arc::new(Mutex::new(Vec::new()));
+        let delays = [30, 10, 20].iter().map(|&millis| {
+            let elapsed = elapsed.clone();
+            async move {
+                ThreadTimer::delay_for(Duration::from_millis(millis)).await;
+                elapsed.lock().unwrap().push(millis);
+            }
+        });
+        let cancelled = ThreadTimer::delay_for(Duration::from_millis(15));
+        block_on(async {
+            let mut cancelled = cancelled;
+            assert!(futures::poll!(&mut cancelled).is_pending());
+            drop(cancelled);
+            futures::future::join_all(delays).await;
+        });
+        assert_eq!(*elapsed.lock().unwrap(), [10, 20, 30]);
+    }
+
+    #[test]
+    fn proc_macros() {
+        #[linked_futures::macros::linked_block(AttributeBlock)]
+        enum AttributeBlockFutureIdentifier {
+            Never,
+            Stop,
+        }
+
+        let block = link_futures!(AttributeBlock, AttributeBlockFutureIdentifier;
+            Never => futures::future::pending::<()>(),
+            Stop => async { }
+        );
+        let (stopped_future_name, _) = block_on(block);
+        assert_eq!(stopped_future_name, AttributeBlockFutureIdentifier::Stop);
+
+        linked_futures::macros::linked!(let block: ProcInlineFutureIdentifier;
+            Never => futures::future::pending::<()>(),
+            Stop => async { }
+        );
+        let (stopped_future_name, _) = block_on(block);
+        assert_eq!(stopped_future_name, ProcInlineFutureIdentifier::Stop);
+
+        #[linked_futures::macros::linked_block(TypedAttributeBlock, TypedAttributeBlockOutput)]
+        enum TypedAttributeBlockFutureIdentifier {
+            Never,
+            Value,
+        }
+
+        let block = link_typed_futures!(
+            TypedAttributeBlock,
+            TypedAttributeBlockFutureIdentifier,
+            TypedAttributeBlockOutput;
+            Never => futures::future::pending::<()>(),
+            Value => async { 1 }
+        );
+        assert_eq!(block_on(block), TypedAttributeBlockOutput::Value(1));
+    }
+
+    /// Subscriber recording the events with the fields of the span they are emitted within
+    #[derive(Default)]
+    struct RecordingSubscriber {
+        spans: std::sync::Mutex<Vec<String>>,
+        entered: std::sync::Mutex<Vec<usize>>,
+        events: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    struct FieldsVisitor(Vec<String>);
+
+    impl tracing::field::Visit for FieldsVisitor {
+        fn record_str(&mut self, _field: &tracing::field::Field, value: &str) {
+            self.0.push(value.to_owned());
+        }
+
+        fn record_debug(&mut self, _field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.push(format!("{:?}", value));
+        }
+    }
+
+    impl tracing::Subscriber for RecordingSubscriber {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut fields = FieldsVisitor(vec![span.metadata().name().to_owned()]);
+            span.record(&mut fields);
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(fields.0.join(" "));
+            tracing::span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut fields = FieldsVisitor(Vec::new());
+            event.record(&mut fields);
+            let span = match self.entered.lock().unwrap().last() {
+                Some(&index) => self.spans.lock().unwrap()[index - 1].clone(),
+                None => String::new(),
+            };
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{}: {}", span, fields.0.join(" ")));
+        }
+
+        fn enter(&self, span: &tracing::span::Id) {
+            self.entered.lock().unwrap().push(span.into_u64() as usize);
+        }
+
+        fn exit(&self, _span: &tracing::span::Id) {
+            self.entered.lock().unwrap().pop();
+        }
+    }
+
+    #[test]
+    fn traced_futures() {
+        let subscriber = RecordingSubscriber::default();
+        let events = subscriber.events.clone();
+        tracing::subscriber::with_default(subscriber, || {
+            let (_tx, rx) = oneshot::channel::<()>();
+            let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+                Never => async {
+                    let _ = rx.await;
+                },
+                Stop => async { }
+            );
+            let (stopped_future_name, _) = block_on(block);
+            assert_eq!(stopped_future_name, SimpleBlockFutureIdentifier::Stop);
+        });
+
+        let mut events = events.lock().unwrap().clone();
+        events.sort();
+        assert_eq!(
+            events,
+            vec![
+                "linked_future SimpleBlock Never: linked future cancelled",
+                "linked_future SimpleBlock Never: linked future started",
+                "linked_future SimpleBlock Stop: linked future completed",
+                "linked_future SimpleBlock Stop: linked future started",
+            ]
+        );
+    }
+
+    #[test]
+    fn metered_futures() {
+        use futures::StreamExt;
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            let (tx, rx) = futures::channel::mpsc::unbounded();
+            let block = link_futures!(SimpleBlock, SimpleBlockFutureIdentifier;
+                #[restart]
+                Never => || {
+                    let tx = tx.clone();
+                    async move { tx.unbounded_send(()).unwrap() }
+                },
+                Stop => rx.take(3).count()
+            );
+            let (stopped_future_name, runs) = block_on(block);
+            assert_eq!(stopped_future_name, SimpleBlockFutureIdentifier::Stop);
+            assert_eq!(runs, 3);
+        });
+
+        let mut metrics = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let key = key.key();
+                let labels = key
+                    .labels()
+                    .map(|label| format!("{}={}", label.key(), label.value()))
+                    .collect::<Vec<_>>();
+                let value = match value {
+                    DebugValue::Counter(count) => count.to_string(),
+                    DebugValue::Histogram(durations) => format!("{} durations", durations.len()),
+                    DebugValue::Gauge(value) => value.to_string(),
+                };
+                format!("{}{{{}}} {}", key.name(), labels.join(","), value)
+            })
+            .collect::<Vec<_>>();
+        metrics.sort();
+        let restarts = metrics.remove(
+            metrics
+                .iter()
+                .position(|metric| metric.starts_with("linked_future_restarts_total"))
+                .unwrap(),
+        );
+        let (restarts, count) = restarts.split_once(' ').unwrap();
+        assert_eq!(
+            restarts,
+            "linked_future_restarts_total{block=SimpleBlock,variant=Never}"
+        );
+        assert!(count.parse::<u64>().unwrap() >= 2);
+        assert_eq!(
+            metrics,
+            vec![
+                "linked_future_duration_seconds{block=SimpleBlock,variant=Never,outcome=cancelled} 1 durations",
+                "linked_future_duration_seconds{block=SimpleBlock,variant=Stop,outcome=completed} 1 durations",
+                "linked_future_terminations_total{block=SimpleBlock,variant=Stop} 1",
+            ]
+        );
+    }
 }
@@ -0,0 +1,82 @@
+//! Linked blocks on `wasm32-unknown-unknown`, coordinating the fetches of a web worker with a
+//! stop requested from the UI
+//!
+//! Run with `wasm-bindgen-test-runner` as the runner of the target:
+//! ```sh
+//! CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=wasm-bindgen-test-runner \
+//!     cargo test --target wasm32-unknown-unknown --features wasm --test wasm
+//! ```
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use futures::channel::oneshot;
+use futures::future::pending;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use linked_futures::{
+    link_futures, linked_block, GlooTimer, LinkedBlockExt, LinkedTaskBlock, LocalSpawner, TimedOut,
+    Timer, WasmSpawner,
+};
+
+linked_block!(Worker, WorkerFutureIdentifier; Fetcher, UiStop);
+
+/// Pretends to fetch `url`, resolving with its length once the response arrives
+async fn fetch(url: &'static str, latency: Duration) -> usize {
+    GlooTimer::delay_for(latency).await;
+    url.len()
+}
+
+#[wasm_bindgen_test]
+async fn ui_stop_cancels_fetches() {
+    let fetched = Rc::new(RefCell::new(Vec::new()));
+    let (stop, stopped) = oneshot::channel();
+
+    let worker_fetched = fetched.clone();
+    let block = link_futures!(Worker, WorkerFutureIdentifier;
+        Fetcher => async move {
+            for url in &["/status", "/items", "/archive"] {
+                let len = fetch(url, Duration::from_millis(20)).await;
+                worker_fetched.borrow_mut().push(len);
+            }
+            pending::<()>().await
+        },
+        UiStop => async move {
+            stopped.await.unwrap()
+        }
+    );
+    WasmSpawner::spawn_local(async move {
+        GlooTimer::delay_for(Duration::from_millis(30)).await;
+        stop.send(()).unwrap();
+    });
+
+    let (completed_future_identifier, ()) = block.await;
+    assert_eq!(completed_future_identifier, WorkerFutureIdentifier::UiStop);
+    assert_eq!(*fetched.borrow(), vec![7]);
+}
+
+#[wasm_bindgen_test]
+async fn with_timeout_on_gloo_timer() {
+    let block = link_futures!(Worker, WorkerFutureIdentifier;
+        Fetcher => fetch("/archive", Duration::from_secs(60)),
+        UiStop => pending()
+    );
+    let result = block
+        .with_timeout(GlooTimer::delay_for(Duration::from_millis(10)))
+        .await;
+    assert_eq!(result, Err(TimedOut));
+}
+
+#[wasm_bindgen_test]
+async fn local_tasks_on_wasm_spawner() {
+    let state = Rc::new("local state");
+    let mut block = LinkedTaskBlock::new();
+    block.spawn_local_on::<WasmSpawner, _>(pending());
+    block.spawn_local_on::<WasmSpawner, _>(async move {
+        fetch("/status", Duration::from_millis(1)).await;
+        *state
+    });
+    assert_eq!(block.await, "local state");
+}